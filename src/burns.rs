@@ -0,0 +1,137 @@
+//! Scripted engine burns for headless runs, loaded from TOML
+
+use crate::det_math;
+use crate::game::Game;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BurnFile {
+    #[serde(default)]
+    burns: Vec<BurnSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BurnSpec {
+    start: f64,    // Game seconds after the run starts
+    duration: f64, // Game seconds
+    vessel: Option<usize>, // Numbered from 1 as on the HUD; the active vessel if omitted
+    direction: Option<String>, // prograde, retrograde, radial-out or radial-in
+    heading: Option<f64>,      // Degrees clockwise from up, instead of a direction
+    #[serde(default = "full_throttle")]
+    throttle: f64,
+}
+
+fn full_throttle() -> f64 {
+    1.0
+}
+
+/// Which way a burn points the vessel
+#[derive(Clone, Copy)]
+enum Pointing {
+    Prograde,
+    Retrograde,
+    RadialOut,
+    RadialIn,
+    Heading(f64), // Radians, in Player::rotation's convention
+}
+
+struct Burn {
+    start: f64,
+    end: f64,
+    vessel: Option<usize>,
+    pointing: Pointing,
+    throttle: f64,
+}
+
+pub struct BurnSchedule {
+    burns: Vec<Burn>,
+}
+
+impl BurnSchedule {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let file: BurnFile = toml::from_str(source).map_err(|e| format!("Invalid burn schedule: {}", e))?;
+        let mut burns = Vec::with_capacity(file.burns.len());
+        for (i, spec) in file.burns.into_iter().enumerate() {
+            let what = format!("Burn {}", i + 1);
+            let pointing = match (spec.direction.as_deref(), spec.heading) {
+                (Some("prograde"), None) => Pointing::Prograde,
+                (Some("retrograde"), None) => Pointing::Retrograde,
+                (Some("radial-out"), None) => Pointing::RadialOut,
+                (Some("radial-in"), None) => Pointing::RadialIn,
+                (None, Some(heading)) => Pointing::Heading(heading.to_radians()),
+                (Some(direction), None) => {
+                    return Err(format!("{} has unknown direction '{}' (expected prograde, retrograde, radial-out or radial-in)", what, direction));
+                }
+                _ => return Err(format!("{} needs exactly one of direction or heading", what)),
+            };
+            if spec.duration < 0.0 || !(0.0..=1.0).contains(&spec.throttle) {
+                return Err(format!("{} needs a duration of at least 0 and a throttle from 0 to 1", what));
+            }
+            if spec.vessel == Some(0) {
+                return Err(format!("{} names vessel 0; vessels are numbered from 1", what));
+            }
+            burns.push(Burn {
+                start: spec.start,
+                end: spec.start + spec.duration,
+                vessel: spec.vessel.map(|vessel| vessel - 1),
+                pointing,
+                throttle: spec.throttle,
+            });
+        }
+        Ok(Self { burns })
+    }
+
+    pub fn load_file(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read burn schedule {}: {}", path, e))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Fire every burn under way at `time` for `dt`, as the thrust key would
+    pub fn apply(&self, game: &mut Game, time: f64, dt: f64) {
+        for burn in self.burns.iter().filter(|burn| burn.start <= time && time < burn.end) {
+            let index = burn.vessel.unwrap_or(game.active_vessel);
+            if index >= game.vessels.len() {
+                continue;
+            }
+            let rotation = match burn.pointing {
+                Pointing::Heading(heading) => Some(heading),
+                pointing => orbital_direction(game, index, pointing),
+            };
+
+            let vessel = &game.vessels[index];
+            let thrust = vessel.engine_thrust * burn.throttle;
+            if let Some(contact) = vessel.landed {
+                // Stay on the surface until thrust overcomes local gravity; a wreck stays for good
+                if contact.crashed || thrust / vessel.mass <= game.surface_gravity(contact.planet_index) {
+                    continue;
+                }
+            }
+
+            let vessel = &mut game.vessels[index];
+            vessel.landed = None;
+            if let Some(rotation) = rotation {
+                vessel.rotation = rotation;
+            }
+            vessel.velocity.x += det_math::sin(vessel.rotation) * thrust / vessel.mass * dt;
+            vessel.velocity.y += -det_math::cos(vessel.rotation) * thrust / vessel.mass * dt;
+        }
+    }
+}
+
+/// Rotation pointing a vessel along its motion or position relative to the body
+/// pulling on it hardest, or None if there is no such body
+fn orbital_direction(game: &Game, index: usize, pointing: Pointing) -> Option<f64> {
+    let vessel = &game.vessels[index];
+    let body = &game.planets[game.dominant_body(&vessel.position)?];
+    let direction = match pointing {
+        Pointing::Prograde => vessel.velocity.subtract(&body.velocity),
+        Pointing::Retrograde => body.velocity.subtract(&vessel.velocity),
+        Pointing::RadialOut => vessel.position.subtract(&body.position),
+        Pointing::RadialIn => body.position.subtract(&vessel.position),
+        Pointing::Heading(heading) => return Some(heading),
+    };
+    // Thrust acts along (sin r, -cos r)
+    Some(det_math::atan2(direction.x, -direction.y))
+}
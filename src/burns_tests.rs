@@ -0,0 +1,88 @@
+//! Burn schedules: what parses, and how the burns push vessels.
+
+use crate::burns::BurnSchedule;
+use crate::game::Game;
+use crate::initial_universe::calculate_stable_orbit;
+use crate::planet::Planet;
+use crate::player::Player;
+use crate::vector2::Vector2;
+
+const MASS: f64 = 1.0e12;
+const DT: f64 = 0.01;
+
+/// Two vessels on circular orbits around a planet at the origin
+fn orbits() -> Game {
+    let origin = Vector2 { x: 0.0, y: 0.0 };
+    let planet = Planet::new("Planet".to_string(), 10.0, MASS, origin, origin, 0x4040FF);
+    let mut game = Game::new(vec![planet], Vec::new());
+    for radius in [1000.0, 2000.0] {
+        let (position, velocity) = calculate_stable_orbit(origin, origin, MASS, radius, 0.0, game.big_gravity);
+        game.vessels.push(Player::new(position, velocity, 2.0, 0.0));
+    }
+    game
+}
+
+fn assert_near(actual: Vector2, expected: Vector2) {
+    assert!(actual.distance(&expected) < 1e-9, "({}, {}) is not ({}, {})", actual.x, actual.y, expected.x, expected.y);
+}
+
+#[test]
+fn heading_burns_fire_while_under_way() {
+    let schedule = BurnSchedule::parse("[[burns]]\nstart = 1.0\nduration = 2.0\nvessel = 2\nheading = 90.0\nthrottle = 0.5\n").unwrap();
+    let mut game = orbits();
+    let before: Vec<Vector2> = game.vessels.iter().map(|vessel| vessel.velocity).collect();
+
+    // Neither before the start nor from the end on
+    for time in [0.5, 3.0] {
+        schedule.apply(&mut game, time, DT);
+    }
+    assert_near(game.vessels[1].velocity, before[1]);
+
+    // Vessels are numbered from 1, and 90 degrees clockwise from up is +x
+    schedule.apply(&mut game, 1.0, DT);
+    let push = game.vessels[1].engine_thrust * 0.5 / game.vessels[1].mass * DT;
+    assert_near(game.vessels[1].velocity, before[1].add(&Vector2 { x: push, y: 0.0 }));
+    assert_near(game.vessels[0].velocity, before[0]);
+}
+
+#[test]
+fn orbital_directions_follow_the_orbit() {
+    let schedule = BurnSchedule::parse(
+        "[[burns]]\nstart = 0.0\nduration = 1.0\ndirection = \"prograde\"\n\n\
+         [[burns]]\nstart = 1.0\nduration = 1.0\ndirection = \"radial-out\"\n",
+    )
+    .unwrap();
+    let mut game = orbits();
+    let vessel = game.vessels[0];
+    let push = vessel.engine_thrust / vessel.mass * DT;
+
+    // The active vessel, at full throttle by default
+    schedule.apply(&mut game, 0.0, DT);
+    assert_near(game.vessels[0].velocity, vessel.velocity.add(&vessel.velocity.normalize().scale(push)));
+
+    let vessel = game.vessels[0];
+    schedule.apply(&mut game, 1.0, DT);
+    assert_near(game.vessels[0].velocity, vessel.velocity.add(&vessel.position.normalize().scale(push)));
+}
+
+#[test]
+fn invalid_schedules_are_refused() {
+    let burn = "[[burns]]\nstart = 0.0\nduration = 1.0\n";
+    for (schedule, error) in [
+        (format!("{}direction = \"sideways\"\n", burn), "unknown direction 'sideways'"),
+        (format!("{}direction = \"prograde\"\nheading = 0.0\n", burn), "exactly one of direction or heading"),
+        (burn.to_string(), "exactly one of direction or heading"),
+        (format!("{}heading = 0.0\nthrottle = 1.5\n", burn), "a throttle from 0 to 1"),
+        ("[[burns]]\nstart = 0.0\nduration = -1.0\nheading = 0.0\n".to_string(), "a duration of at least 0"),
+        (format!("{}heading = 0.0\nvessel = 0\n", burn), "numbered from 1"),
+        (format!("{}heading = 0.0\nfuel = 3\n", burn), "Invalid burn schedule"),
+    ] {
+        match BurnSchedule::parse(&schedule) {
+            Ok(_) => panic!("Accepted:\n{}", schedule),
+            Err(message) => assert!(message.contains(error), "'{}' does not mention '{}'", message, error),
+        }
+    }
+    // Errors name the burn, counting from 1
+    let second = format!("{}heading = 0.0\n\n{}direction = \"up\"\n", burn, burn);
+    assert!(BurnSchedule::parse(&second).err().is_some_and(|message| message.starts_with("Burn 2 ")));
+}
@@ -0,0 +1,139 @@
+//! Capture of flights as an animated GIF or a numbered PNG sequence, rendered offscreen
+
+use crate::game::Game;
+use crate::offscreen::render_image;
+use crate::render::Camera;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame, RgbImage};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+
+#[allow(dead_code)]
+pub const DEFAULT_CAPTURE_PATH: &str = "spaceflight_elephant_capture.gif";
+pub const DEFAULT_CAPTURE_INTERVAL: usize = 10; // Render every Nth frame
+pub const DEFAULT_CAPTURE_DELAY_MS: u32 = 100;  // Between GIF frames when played back
+
+// GIF color quantization from 1 (best) to 30 (fastest)
+const GIF_SPEED: i32 = 10;
+
+// Rendered frames that can wait for the writer thread before rendering waits for it
+const QUEUED_FRAMES: usize = 8;
+
+pub struct CaptureOptions {
+    pub interval: usize,
+    pub delay_ms: u32,
+}
+
+impl CaptureOptions {
+    pub fn new() -> Self {
+        Self { interval: DEFAULT_CAPTURE_INTERVAL, delay_ms: DEFAULT_CAPTURE_DELAY_MS }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_interval(mut self, interval: usize) -> Self {
+        self.interval = interval.max(1);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_delay(mut self, delay_ms: u32) -> Self {
+        self.delay_ms = delay_ms;
+        self
+    }
+}
+
+/// Where captured frames go
+enum Sink {
+    Gif(Box<GifEncoder<BufWriter<File>>>), // Frames are encoded as they come
+    Png(String),                           // Path prefix; frames are written as <prefix>_00000.png, ...
+}
+
+impl Sink {
+    fn write(&mut self, image: RgbImage, index: usize, delay_ms: u32, path: &str) -> Result<(), String> {
+        match self {
+            Sink::Gif(encoder) => {
+                let delay = Delay::from_numer_denom_ms(delay_ms, 1);
+                let frame = Frame::from_parts(DynamicImage::ImageRgb8(image).into_rgba8(), 0, 0, delay);
+                encoder.encode_frame(frame).map_err(|e| format!("Failed to write {}: {}", path, e))
+            }
+            Sink::Png(prefix) => {
+                let path = format!("{}_{:05}.png", prefix, index);
+                image.save(&path).map_err(|e| format!("Failed to write {}: {}", path, e))
+            }
+        }
+    }
+}
+
+/// A capture in progress. Frames are rendered on the caller's thread and
+/// encoded on a writer thread, so the game keeps its frame rate while recording.
+pub struct Capture {
+    path: String,
+    width: usize,
+    height: usize,
+    interval: usize,
+    frames: SyncSender<RgbImage>,
+    writer: JoinHandle<Result<(), String>>,
+    frames_seen: usize,
+    pub frames_written: usize,
+}
+
+impl Capture {
+    /// Start a capture to `path`: an animated GIF if it ends in .gif, numbered PNG
+    /// files beside it if it ends in .png. Every frame has the size given here.
+    pub fn start(path: &str, width: usize, height: usize, options: CaptureOptions) -> Result<Self, String> {
+        let mut sink = if path.ends_with(".gif") {
+            let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), GIF_SPEED);
+            encoder.set_repeat(Repeat::Infinite).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            Sink::Gif(Box::new(encoder))
+        } else if let Some(prefix) = path.strip_suffix(".png") {
+            if let Some(directory) = Path::new(prefix).parent().filter(|directory| !directory.as_os_str().is_empty()) {
+                std::fs::create_dir_all(directory).map_err(|e| format!("Failed to create {}: {}", directory.display(), e))?;
+            }
+            Sink::Png(prefix.to_string())
+        } else {
+            return Err(format!("Capture {} must end in .gif or .png", path));
+        };
+
+        let (frames, queue) = mpsc::sync_channel::<RgbImage>(QUEUED_FRAMES);
+        let writer_path = path.to_string();
+        let writer = thread::spawn(move || {
+            for (index, image) in queue.into_iter().enumerate() {
+                sink.write(image, index, options.delay_ms, &writer_path)?;
+            }
+            Ok(())
+        });
+        Ok(Self { path: path.to_string(), width, height, interval: options.interval, frames, writer, frames_seen: 0, frames_written: 0 })
+    }
+
+    /// Count one simulation frame, returning whether it is one to render
+    pub fn next_frame(&mut self) -> bool {
+        let due = self.frames_seen.is_multiple_of(self.interval);
+        self.frames_seen += 1;
+        due
+    }
+
+    /// Render a frame through the camera, at the size the capture started with
+    pub fn write(&mut self, game: &Game, camera: &Camera) -> Result<(), String> {
+        let camera = Camera { width: self.width, height: self.height, ..*camera };
+        let image = render_image(game, &camera)?;
+        // The writer only hangs up after failing, and finish reports why
+        if self.frames.send(image).is_ok() {
+            self.frames_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Wait for the last frames to be written, then describe what was
+    pub fn finish(self) -> Result<String, String> {
+        drop(self.frames);
+        self.writer.join().map_err(|_| format!("Capture to {} failed", self.path))??;
+        match self.path.strip_suffix(".png") {
+            Some(prefix) => Ok(format!("Captured {} frames to {}_00000.png onwards", self.frames_written, prefix)),
+            None => Ok(format!("Captured {} frames to {}", self.frames_written, self.path)),
+        }
+    }
+}
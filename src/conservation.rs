@@ -0,0 +1,47 @@
+//! Conserved quantities of the whole system, for judging integrator accuracy
+
+use crate::game::Game;
+use crate::planet::BodyRole;
+use crate::vector2::Vector2;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Serialize)]
+pub struct Conserved {
+    pub energy: f64,           // Kinetic plus gravitational potential
+    pub momentum: Vector2,
+    pub angular_momentum: f64, // About the origin
+}
+
+/// Measure the quantities Game::update conserves when no thrust or sunlight acts
+pub fn measure(game: &Game) -> Conserved {
+    let masses = game.planets.iter().map(|planet| (planet.mass, planet.position, planet.velocity));
+    let masses: Vec<(f64, Vector2, Vector2)> = masses.chain(game.vessels.iter().map(|vessel| (vessel.mass, vessel.position, vessel.velocity))).collect();
+
+    let mut energy = 0.0;
+    let mut momentum = Vector2 { x: 0.0, y: 0.0 };
+    let mut angular_momentum = 0.0;
+    for &(mass, position, velocity) in &masses {
+        energy += 0.5 * mass * velocity.dot(&velocity);
+        momentum = momentum.add(&velocity.scale(mass));
+        angular_momentum += mass * position.cross(&velocity);
+    }
+
+    // Every pair of planets attracts, and every non-barycenter planet attracts every vessel
+    for (i, a) in game.planets.iter().enumerate() {
+        for b in &game.planets[i + 1..] {
+            energy -= game.big_gravity * a.mass * b.mass / a.position.distance(&b.position);
+        }
+        if a.role != BodyRole::Barycenter {
+            for vessel in &game.vessels {
+                energy -= game.big_gravity * a.mass * vessel.mass / a.position.distance(&vessel.position);
+            }
+        }
+    }
+
+    Conserved { energy, momentum, angular_momentum }
+}
+
+/// Change from `initial`, relative to its size where that is meaningful
+pub fn relative_change(initial: f64, last: f64) -> f64 {
+    if initial == 0.0 { last - initial } else { (last - initial) / initial.abs() }
+}
@@ -0,0 +1,153 @@
+#![allow(clippy::excessive_precision)]
+//! Deterministic math module - software trig built only from IEEE 754 basic
+//! operations, so results are bit-identical on native and wasm targets.
+//! Square roots are left to `f64::sqrt`, which IEEE 754 (and the wasm spec)
+//! requires to be correctly rounded.
+
+use std::f64::consts::{FRAC_2_PI, FRAC_PI_2, FRAC_PI_4, PI};
+
+// pi/2 split into a high part and a tail for argument reduction (from fdlibm)
+const PIO2_HI: f64 = 1.57079632673412561417e+00;
+const PIO2_LO: f64 = 6.07710050650619224932e-11;
+
+// Minimax polynomial coefficients on [-pi/4, pi/4] (from fdlibm)
+const S1: f64 = -1.66666666666666324348e-01;
+const S2: f64 = 8.33333333332248946124e-03;
+const S3: f64 = -1.98412698298579493134e-04;
+const S4: f64 = 2.75573137070700676789e-06;
+const S5: f64 = -2.50507602534068634195e-08;
+const S6: f64 = 1.58969099521155010221e-10;
+
+const C1: f64 = 4.16666666666666019037e-02;
+const C2: f64 = -1.38888888888741095749e-03;
+const C3: f64 = 2.48015872894767294178e-05;
+const C4: f64 = -2.75573143513906633035e-07;
+const C5: f64 = 2.08757232129817482790e-09;
+const C6: f64 = -1.13596475577881948265e-11;
+
+const ATAN_HI: [f64; 4] = [
+    4.63647609000806093515e-01,
+    FRAC_PI_4,
+    9.82793723247329054082e-01,
+    FRAC_PI_2,
+];
+const ATAN_LO: [f64; 4] = [
+    2.26987774529616870924e-17,
+    3.06161699786838301793e-17,
+    1.39033110312309984516e-17,
+    6.12323399573676603587e-17,
+];
+const AT: [f64; 11] = [
+    3.33333333333329318027e-01,
+    -1.99999999998764832476e-01,
+    1.42857142725034663711e-01,
+    -1.11111104054623557880e-01,
+    9.09088713343650656196e-02,
+    -7.69187620504482999495e-02,
+    6.66107313738753120669e-02,
+    -5.83357013379057348645e-02,
+    4.97687799461593236017e-02,
+    -3.65315727442169155270e-02,
+    1.62858201153657823623e-02,
+];
+
+fn kernel_sin(x: f64) -> f64 {
+    let z = x * x;
+    x + x * z * (S1 + z * (S2 + z * (S3 + z * (S4 + z * (S5 + z * S6)))))
+}
+
+fn kernel_cos(x: f64) -> f64 {
+    let z = x * x;
+    1.0 - 0.5 * z + z * z * (C1 + z * (C2 + z * (C3 + z * (C4 + z * (C5 + z * C6)))))
+}
+
+/// Reduce x to r in [-pi/4, pi/4] and the quadrant of x
+fn reduce(x: f64) -> (f64, i64) {
+    let k = (x * FRAC_2_PI).round();
+    let r = (x - k * PIO2_HI) - k * PIO2_LO;
+    (r, (k as i64).rem_euclid(4))
+}
+
+pub fn sin(x: f64) -> f64 {
+    let (r, quadrant) = reduce(x);
+    match quadrant {
+        0 => kernel_sin(r),
+        1 => kernel_cos(r),
+        2 => -kernel_sin(r),
+        _ => -kernel_cos(r),
+    }
+}
+
+pub fn cos(x: f64) -> f64 {
+    let (r, quadrant) = reduce(x);
+    match quadrant {
+        0 => kernel_cos(r),
+        1 => -kernel_sin(r),
+        2 => -kernel_cos(r),
+        _ => kernel_sin(r),
+    }
+}
+
+pub fn atan(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let mut t = x.abs();
+
+    // Shift the argument near one of the tabulated breakpoints
+    let id = if t < 0.4375 {
+        None
+    } else if t < 0.6875 {
+        t = (2.0 * t - 1.0) / (2.0 + t);
+        Some(0)
+    } else if t < 1.1875 {
+        t = (t - 1.0) / (t + 1.0);
+        Some(1)
+    } else if t < 2.4375 {
+        t = (t - 1.5) / (1.0 + 1.5 * t);
+        Some(2)
+    } else {
+        t = -1.0 / t;
+        Some(3)
+    };
+
+    let z = t * t;
+    let w = z * z;
+    let s1 = z * (AT[0] + w * (AT[2] + w * (AT[4] + w * (AT[6] + w * (AT[8] + w * AT[10])))));
+    let s2 = w * (AT[1] + w * (AT[3] + w * (AT[5] + w * (AT[7] + w * AT[9]))));
+
+    let result = match id {
+        None => t - t * (s1 + s2),
+        Some(i) => ATAN_HI[i] - ((t * (s1 + s2) - ATAN_LO[i]) - t),
+    };
+    sign * result
+}
+
+pub fn atan2(y: f64, x: f64) -> f64 {
+    if x > 0.0 {
+        atan(y / x)
+    } else if x < 0.0 {
+        if y >= 0.0 {
+            atan(y / x) + PI
+        } else {
+            atan(y / x) - PI
+        }
+    } else if y > 0.0 {
+        FRAC_PI_2
+    } else if y < 0.0 {
+        -FRAC_PI_2
+    } else {
+        0.0
+    }
+}
+
+/// Cube root by Newton iteration from a bit-level first guess
+pub fn cbrt(x: f64) -> f64 {
+    if x == 0.0 || !x.is_finite() {
+        return x;
+    }
+    let a = x.abs();
+    let mut y = f64::from_bits(a.to_bits() / 3 + 0x2A9F_7893_782D_A1CE);
+    for _ in 0..6 {
+        y -= (y * y * y - a) / (3.0 * y * y);
+    }
+    if x < 0.0 { -y } else { y }
+}
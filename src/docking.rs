@@ -0,0 +1,224 @@
+//! Docking ports, capture and undocking between vessels
+
+use crate::game::Game;
+use crate::player::{Player, PLAYER_FOOTPRINT};
+use crate::vector2::Vector2;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+// Capture tolerances
+pub const DOCKING_CAPTURE_DISTANCE: f64 = 4.0;
+pub const DOCKING_MAX_CLOSING_SPEED: f64 = 2.0;
+pub const DOCKING_MAX_ANGLE_ERROR: f64 = 0.15; // radians
+
+// Only show the docking HUD for targets closer than this
+const DOCKING_HUD_RANGE: f64 = 500.0;
+
+// Speed at which the two halves drift apart after undocking
+#[allow(dead_code)]
+const UNDOCK_SEPARATION_SPEED: f64 = 1.0;
+
+/// A docking port fixed to a vessel, in the vessel's local frame
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct DockingPort {
+    pub offset: Vector2,  // Position relative to the vessel center (nose is -y)
+    pub orientation: f64, // Facing angle relative to the nose
+}
+
+/// A vessel merged into another after capture, kept so it can be split off again
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct DockedVessel {
+    pub mass: f64,
+    pub port: DockingPort,
+    pub offset: Vector2, // Center relative to the combined center of mass, local frame
+    pub rotation_offset: f64,
+    pub engine_thrust: f64,
+    pub sail_area: f64, // Sail area to mass ratio times mass
+}
+
+/// Relative state of the active vessel's port against a target port
+pub struct DockingStatus {
+    pub target: usize,
+    pub relative_position: Vector2,
+    pub relative_velocity: Vector2,
+    pub closing_speed: f64,
+    pub angle_error: f64,
+}
+
+impl DockingPort {
+    /// Port at the tip of the nose, facing forward
+    pub fn nose() -> Self {
+        Self {
+            offset: Vector2 { x: 0.0, y: -PLAYER_FOOTPRINT },
+            orientation: 0.0,
+        }
+    }
+
+    pub fn world_position(&self, vessel: &Player) -> Vector2 {
+        vessel.position.add(&self.offset.rotate(vessel.rotation))
+    }
+
+    pub fn world_facing(&self, vessel: &Player) -> f64 {
+        vessel.rotation + self.orientation
+    }
+}
+
+impl DockingStatus {
+    pub fn within_tolerances(&self) -> bool {
+        self.relative_position.magnitude() <= DOCKING_CAPTURE_DISTANCE
+            && self.relative_velocity.magnitude() <= DOCKING_MAX_CLOSING_SPEED
+            && self.angle_error.abs() <= DOCKING_MAX_ANGLE_ERROR
+    }
+}
+
+/// Wrap an angle into [-PI, PI]
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Find the nearest target port in range of the active vessel's port
+pub fn docking_status(game: &Game) -> Option<DockingStatus> {
+    let active = game.player();
+    let own_port = active.port.world_position(active);
+    let own_facing = active.port.world_facing(active);
+
+    let mut best: Option<DockingStatus> = None;
+    for (i, vessel) in game.vessels.iter().enumerate() {
+        if i == game.active_vessel {
+            continue;
+        }
+
+        let relative_position = vessel.port.world_position(vessel).subtract(&own_port);
+        let distance = relative_position.magnitude();
+        if distance > DOCKING_HUD_RANGE {
+            continue;
+        }
+        if let Some(current) = &best
+            && current.relative_position.magnitude() <= distance
+        {
+            continue;
+        }
+
+        let relative_velocity = vessel.velocity.subtract(&active.velocity);
+        let closing_speed = if distance > 0.0 {
+            -relative_velocity.dot(&relative_position) / distance
+        } else {
+            0.0
+        };
+
+        // Ports must face each other head on
+        let angle_error = wrap_angle(vessel.port.world_facing(vessel) - own_facing - PI);
+
+        best = Some(DockingStatus {
+            target: i,
+            relative_position,
+            relative_velocity,
+            closing_speed,
+            angle_error,
+        });
+    }
+
+    best
+}
+
+impl Game {
+    /// Release the undocking latch of every vessel whose port has moved out of
+    /// capture distance of all the other ports
+    fn release_undock_latches(&mut self) {
+        let ports: Vec<Vector2> = self.vessels.iter().map(|vessel| vessel.port.world_position(vessel)).collect();
+        for (i, vessel) in self.vessels.iter_mut().enumerate() {
+            let clear = ports.iter().enumerate().all(|(j, port)| i == j || port.distance(&ports[i]) > DOCKING_CAPTURE_DISTANCE);
+            if clear {
+                vessel.undock_latched = false;
+            }
+        }
+    }
+
+    /// Capture the nearest target if it is within docking tolerances
+    pub fn try_dock(&mut self) -> bool {
+        self.release_undock_latches();
+
+        let Some(status) = docking_status(self) else {
+            return false;
+        };
+        if !status.within_tolerances() {
+            return false;
+        }
+
+        let active = *self.player();
+        let target = self.vessels[status.target];
+        // A vessel only carries one docked partner, so neither side may already be a docked pair
+        if active.docked.is_some() || target.docked.is_some() || active.landed.is_some() || target.landed.is_some() {
+            return false;
+        }
+        // Two halves that just split apart drift out of range before they can dock again
+        if active.undock_latched || target.undock_latched {
+            return false;
+        }
+
+        // Combined rigid body at the shared center of mass
+        let total_mass = active.mass + target.mass;
+        let position = active.position.scale(active.mass).add(&target.position.scale(target.mass)).scale(1.0 / total_mass);
+        let velocity = active.velocity.scale(active.mass).add(&target.velocity.scale(target.mass)).scale(1.0 / total_mass);
+
+        let combined = self.player_mut();
+        combined.docked = Some(DockedVessel {
+            mass: target.mass,
+            port: target.port,
+            offset: target.position.subtract(&position).rotate(-active.rotation),
+            rotation_offset: target.rotation - active.rotation,
+            engine_thrust: target.engine_thrust,
+            sail_area: target.sail_area_to_mass * target.mass,
+        });
+        combined.sail_area_to_mass = (active.sail_area_to_mass * active.mass + target.sail_area_to_mass * target.mass) / total_mass;
+        combined.mass = total_mass;
+        combined.position = position;
+        combined.velocity = velocity;
+
+        self.vessels.remove(status.target);
+        if status.target < self.active_vessel {
+            self.active_vessel -= 1;
+        }
+
+        self.recalculate_trajectories();
+        true
+    }
+
+    /// Split the active vessel back into its two halves
+    #[allow(dead_code)]
+    pub fn undock(&mut self) {
+        let combined = *self.player();
+        let Some(docked) = combined.docked else {
+            return;
+        };
+
+        let own_mass = combined.mass - docked.mass;
+        let offset = docked.offset.rotate(combined.rotation);
+        let direction = offset.normalize();
+
+        // Push apart along the line between the centers, conserving momentum
+        let target = Player {
+            position: combined.position.add(&offset),
+            velocity: combined.velocity.add(&direction.scale(UNDOCK_SEPARATION_SPEED * own_mass / combined.mass)),
+            mass: docked.mass,
+            rotation: combined.rotation + docked.rotation_offset,
+            landed: None,
+            port: docked.port,
+            docked: None,
+            undock_latched: true,
+            engine_thrust: docked.engine_thrust,
+            sail_area_to_mass: docked.sail_area / docked.mass,
+        };
+
+        let active = self.player_mut();
+        active.position = combined.position.subtract(&offset.scale(docked.mass / own_mass));
+        active.velocity = combined.velocity.subtract(&direction.scale(UNDOCK_SEPARATION_SPEED * docked.mass / combined.mass));
+        active.sail_area_to_mass = (combined.sail_area_to_mass * combined.mass - docked.sail_area) / own_mass;
+        active.mass = own_mass;
+        active.docked = None;
+        active.undock_latched = true;
+
+        self.vessels.push(target);
+        self.recalculate_trajectories();
+    }
+}
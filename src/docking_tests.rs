@@ -0,0 +1,107 @@
+//! Docking capture and undocking between two vessels.
+
+use crate::docking::{docking_status, DOCKING_MAX_ANGLE_ERROR, DOCKING_MAX_CLOSING_SPEED};
+use crate::game::Game;
+use crate::player::{Player, PLAYER_FOOTPRINT};
+use crate::vector2::Vector2;
+use std::f64::consts::PI;
+
+/// Two vessels at rest with their nose ports touching head on
+fn nose_to_nose() -> Game {
+    approach(Vector2 { x: 0.0, y: 0.0 }, Vector2 { x: 0.0, y: 0.0 }, PI)
+}
+
+/// Two vessels with their nose ports touching, the target turned to `target_rotation`
+fn approach(chaser_velocity: Vector2, target_velocity: Vector2, target_rotation: f64) -> Game {
+    let chaser = Player::new(Vector2 { x: 0.0, y: 0.0 }, chaser_velocity, 1.0, 0.0);
+    let target = Player::new(Vector2 { x: 0.0, y: -16.0 }, target_velocity, 2.0, target_rotation);
+    let mut game = Game::new(vec![], vec![chaser, target]);
+    game.prediction_steps = 100;
+    game.recalculate_trajectories();
+    game
+}
+
+/// One game tick as the window runs it
+fn frame(game: &mut Game) {
+    game.advance_trajectory();
+    game.extend_trajectories(1);
+    game.try_dock();
+}
+
+#[test]
+fn dock_and_undock() {
+    let mut game = nose_to_nose();
+    assert!(game.try_dock());
+    assert_eq!(game.vessels.len(), 1);
+
+    game.undock();
+    assert_eq!(game.vessels.len(), 2);
+    assert_eq!(game.vessels.iter().map(|vessel| vessel.mass).sum::<f64>(), 3.0);
+}
+
+#[test]
+fn undocked_halves_do_not_redock() {
+    let mut game = nose_to_nose();
+    game.try_dock();
+    game.undock();
+    frame(&mut game);
+    assert_eq!(game.vessels.len(), 2);
+
+    // The halves drift apart rather than docking again, and clearing each other's ports releases the latches
+    for _ in 0..400 {
+        frame(&mut game);
+    }
+    assert_eq!(game.vessels.len(), 2);
+    assert!(game.vessels.iter().all(|vessel| !vessel.undock_latched));
+}
+
+/// Total momentum of every vessel
+fn momentum(game: &Game) -> Vector2 {
+    game.vessels.iter().fold(Vector2 { x: 0.0, y: 0.0 }, |sum, vessel| sum.add(&vessel.velocity.scale(vessel.mass)))
+}
+
+fn assert_same(actual: Vector2, expected: Vector2) {
+    assert!(actual.distance(&expected) < 1e-12, "({}, {}) is not ({}, {})", actual.x, actual.y, expected.x, expected.y);
+}
+
+#[test]
+fn fast_approach_is_refused() {
+    let mut game = approach(Vector2 { x: 0.0, y: -1.5 * DOCKING_MAX_CLOSING_SPEED }, Vector2 { x: 0.0, y: 0.0 }, PI);
+    assert!(docking_status(&game).is_some_and(|status| !status.within_tolerances()));
+    assert!(!game.try_dock());
+    assert_eq!(game.vessels.len(), 2);
+}
+
+#[test]
+fn misaligned_approach_is_refused() {
+    let mut game = approach(Vector2 { x: 0.0, y: 0.0 }, Vector2 { x: 0.0, y: 0.0 }, PI + 2.0 * DOCKING_MAX_ANGLE_ERROR);
+    assert!(docking_status(&game).is_some_and(|status| !status.within_tolerances()));
+    assert!(!game.try_dock());
+    assert_eq!(game.vessels.len(), 2);
+}
+
+#[test]
+fn docking_and_undocking_conserve_momentum() {
+    let mut game = approach(Vector2 { x: 3.0, y: -0.5 }, Vector2 { x: 3.5, y: 0.5 }, PI);
+    let before = momentum(&game);
+
+    assert!(game.try_dock());
+    assert_same(momentum(&game), before);
+
+    game.undock();
+    assert_same(momentum(&game), before);
+}
+
+#[test]
+fn docked_pair_refuses_a_third_vessel() {
+    let mut game = nose_to_nose();
+    game.try_dock();
+    let pair = *game.player();
+    let port = pair.port.world_position(&pair);
+    let third = Player::new(port.add(&Vector2 { x: 0.0, y: -PLAYER_FOOTPRINT }), pair.velocity, 1.0, pair.rotation + PI);
+    game.vessels.push(third);
+
+    assert!(docking_status(&game).is_some_and(|status| status.within_tolerances()));
+    assert!(!game.try_dock());
+    assert_eq!(game.vessels.len(), 2);
+}
@@ -0,0 +1,513 @@
+//! Ephemerides: the predicted trajectories of the player and every planet
+//! exported as time, position and velocity tables for analysis outside the
+//! game, and external state tables imported as initial conditions
+
+use crate::game::{Game, TRAJECTORY_DT};
+use crate::units::{UnitSystem, ASTRONOMICAL_UNIT};
+use crate::vector2::Vector2;
+use serde::{Deserialize, Serialize};
+
+// Keep every n-th predicted step when no decimation is given
+pub const DEFAULT_EPHEMERIS_DECIMATION: usize = 10;
+
+// Where the native build writes its ephemeris, with .csv or .json added
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(dead_code)]
+pub const DEFAULT_EPHEMERIS_PATH: &str = "spaceflight_elephant_ephemeris";
+
+// Name of the active vessel's track
+const PLAYER_TRACK: &str = "Player";
+
+// Tilt of the Earth's equator to the ecliptic at J2000, for projecting equatorial tables
+const J2000_OBLIQUITY_DEGREES: f64 = 23.4392911;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EphemerisFormat {
+    Csv,
+    Json,
+}
+
+impl EphemerisFormat {
+    /// Pick the format from a file extension
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit_once('.')?.1;
+        if extension.eq_ignore_ascii_case("csv") {
+            Some(EphemerisFormat::Csv)
+        } else if extension.eq_ignore_ascii_case("json") {
+            Some(EphemerisFormat::Json)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(dead_code)]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EphemerisFormat::Csv => "csv",
+            EphemerisFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct EphemerisOptions {
+    pub decimation: usize,    // Keep every n-th predicted step
+    pub frame: Option<usize>, // Body the positions and velocities are taken relative to
+}
+
+impl EphemerisOptions {
+    pub fn new() -> Self {
+        Self { decimation: DEFAULT_EPHEMERIS_DECIMATION, frame: None }
+    }
+
+    #[allow(dead_code)] // Set by the simulator and the web build; the window keeps the default
+    pub fn with_decimation(mut self, decimation: usize) -> Self {
+        self.decimation = decimation.max(1);
+        self
+    }
+
+    pub fn relative_to(mut self, frame: Option<usize>) -> Self {
+        self.frame = frame;
+        self
+    }
+}
+
+/// One body's states, one entry per row of Ephemeris::time
+#[derive(Serialize, Deserialize)]
+pub struct EphemerisTrack {
+    pub name: String,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub vx: Vec<f64>,
+    pub vy: Vec<f64>,
+}
+
+impl EphemerisTrack {
+    fn new(name: &str) -> Self {
+        Self { name: name.to_string(), x: Vec::new(), y: Vec::new(), vx: Vec::new(), vy: Vec::new() }
+    }
+
+    fn push(&mut self, position: Vector2, velocity: Vector2) {
+        self.x.push(position.x);
+        self.y.push(position.y);
+        self.vx.push(velocity.x);
+        self.vy.push(velocity.y);
+    }
+}
+
+/// Predicted states in game units, with the scales that turn them into SI
+#[derive(Serialize, Deserialize)]
+pub struct Ephemeris {
+    pub frame: Option<String>, // Name of the reference body, or None for the absolute frame
+    pub units: UnitSystem,
+    pub time: Vec<f64>, // Game seconds since the simulation started
+    pub tracks: Vec<EphemerisTrack>, // The player first, then the planets in order
+}
+
+/// Find a body by name, ignoring case, for choosing a frame from the command line
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(dead_code)]
+pub fn body_by_name(game: &Game, name: &str) -> Option<usize> {
+    game.planets.iter().position(|planet| planet.name.eq_ignore_ascii_case(name))
+}
+
+impl Ephemeris {
+    /// Sample the prediction cache. Its entry i is the state after step tick + i + 1.
+    pub fn from_game(game: &Game, options: &EphemerisOptions) -> Result<Self, String> {
+        let cache = &game.cached_trajectories;
+        let player = game.active_vessel;
+        if !cache.is_valid || player >= cache.vessel_positions.len() {
+            return Err("No predicted trajectories to export".to_string());
+        }
+        let frame = options.frame.filter(|&body| body < game.planets.len());
+
+        let mut time = Vec::new();
+        let mut tracks = vec![EphemerisTrack::new(PLAYER_TRACK)];
+        tracks.extend(game.planets.iter().map(|planet| EphemerisTrack::new(&planet.name)));
+
+        let steps = cache.vessel_positions[player].len();
+        for step in (0..steps).step_by(options.decimation.max(1)) {
+            let (origin, origin_velocity) = match frame {
+                Some(body) => (cache.planet_positions[body][step], cache.planet_velocities[body][step]),
+                None => (Vector2 { x: 0.0, y: 0.0 }, Vector2 { x: 0.0, y: 0.0 }),
+            };
+            let relative = |position: Vector2, velocity: Vector2| (position.subtract(&origin), velocity.subtract(&origin_velocity));
+
+            time.push((game.tick + step as u64 + 1) as f64 * TRAJECTORY_DT);
+            let (position, velocity) = relative(cache.vessel_positions[player][step], cache.vessel_velocities[player][step]);
+            tracks[0].push(position, velocity);
+            for (i, track) in tracks[1..].iter_mut().enumerate() {
+                let (position, velocity) = relative(cache.planet_positions[i][step], cache.planet_velocities[i][step]);
+                track.push(position, velocity);
+            }
+        }
+
+        Ok(Self {
+            frame: frame.map(|body| game.planets[body].name.clone()),
+            units: game.units,
+            time,
+            tracks,
+        })
+    }
+
+    /// One row per body per sample: time,body,x,y,vx,vy
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time,body,x,y,vx,vy\n");
+        for track in &self.tracks {
+            let name = csv_field(&track.name);
+            for (i, time) in self.time.iter().enumerate() {
+                csv.push_str(&format!("{},{},{},{},{},{}\n", time, name, track.x[i], track.y[i], track.vx[i], track.vy[i]));
+            }
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to export ephemeris: {}", e))
+    }
+
+    pub fn to_text(&self, format: EphemerisFormat) -> Result<String, String> {
+        match format {
+            EphemerisFormat::Csv => Ok(self.to_csv()),
+            EphemerisFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Write to disk in the format named by the path's extension
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_file(&self, path: &str) -> Result<(), String> {
+        let format = EphemerisFormat::from_path(path).ok_or_else(|| format!("Ephemeris {} must end in .csv or .json", path))?;
+        let text = self.to_text(format)?;
+        std::fs::write(path, text).map_err(|e| format!("Failed to write ephemeris {}: {}", path, e))
+    }
+}
+
+/// Quote a CSV field when it holds a separator or quote
+pub fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Plane an imported table's x and y axes lie in
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReferencePlane {
+    Ecliptic,
+    Equatorial, // Earth's mean equator, as in ICRF tables
+}
+
+impl ReferencePlane {
+    pub fn parse(plane: &str) -> Result<Self, String> {
+        match plane {
+            "ecliptic" => Ok(ReferencePlane::Ecliptic),
+            "equatorial" => Ok(ReferencePlane::Equatorial),
+            _ => Err(format!("unknown plane '{}' (expected ecliptic or equatorial)", plane)),
+        }
+    }
+
+    /// Project a 3D vector into the 2D ecliptic plane, dropping the height above it
+    fn project(&self, [x, y, z]: [f64; 3]) -> Vector2 {
+        match self {
+            ReferencePlane::Ecliptic => Vector2 { x, y },
+            ReferencePlane::Equatorial => {
+                let (sin, cos) = J2000_OBLIQUITY_DEGREES.to_radians().sin_cos();
+                Vector2 { x, y: y * cos + z * sin }
+            }
+        }
+    }
+}
+
+/// Meters in a table length unit: m, km or au
+pub fn length_unit_m(unit: &str) -> Result<f64, String> {
+    match unit.to_ascii_lowercase().as_str() {
+        "m" => Ok(1.0),
+        "km" => Ok(1000.0),
+        "au" => Ok(ASTRONOMICAL_UNIT),
+        _ => Err(format!("unknown length unit '{}' (expected m, km or au)", unit)),
+    }
+}
+
+/// Seconds in a table time unit: s or day
+pub fn time_unit_s(unit: &str) -> Result<f64, String> {
+    match unit.to_ascii_lowercase().as_str() {
+        "s" => Ok(1.0),
+        "d" | "day" => Ok(SECONDS_PER_DAY),
+        _ => Err(format!("unknown time unit '{}' (expected s or day)", unit)),
+    }
+}
+
+/// One row of an imported table, in the table's own units
+pub struct TableState {
+    pub body: String,
+    pub time: f64,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+/// States read from a CSV table or a JPL Horizons vector table. Units and
+/// plane are None unless the table states them itself.
+pub struct EphemerisTable {
+    pub states: Vec<TableState>,
+    pub length_m: Option<f64>,
+    pub time_s: Option<f64>,
+    pub plane: Option<ReferencePlane>,
+}
+
+/// How to turn imported tables into game states. Units and plane override
+/// what the tables state; without either, tables are taken to be in game
+/// units and in the ecliptic.
+pub struct ImportSettings {
+    pub epoch: Option<f64>, // Time column value to start from; each body's first row if None
+    pub length_m: Option<f64>,
+    pub time_s: Option<f64>,
+    pub plane: Option<ReferencePlane>,
+    pub center: Option<String>, // Body moved to rest at the origin
+}
+
+/// Game-unit states by body name, ready to place bodies with
+pub struct ImportedStates {
+    states: Vec<(String, Vector2, Vector2)>,
+}
+
+impl ImportedStates {
+    pub fn new() -> Self {
+        Self { states: Vec::new() }
+    }
+
+    /// Position and velocity of a body, matching its name without regard to case
+    pub fn get(&self, name: &str) -> Option<(Vector2, Vector2)> {
+        self.states
+            .iter()
+            .find(|(body, _, _)| body.eq_ignore_ascii_case(name))
+            .map(|&(_, position, velocity)| (position, velocity))
+    }
+
+    /// Convert every body's state at the epoch into the game's units and plane
+    pub fn from_tables(tables: &[EphemerisTable], settings: &ImportSettings, units: &UnitSystem) -> Result<Self, String> {
+        let mut states: Vec<(String, Vector2, Vector2)> = Vec::new();
+        for table in tables {
+            let length_m = settings.length_m.or(table.length_m).unwrap_or(units.length_m);
+            let time_s = settings.time_s.or(table.time_s).unwrap_or(units.time_s);
+            let plane = settings.plane.or(table.plane).unwrap_or(ReferencePlane::Ecliptic);
+
+            for body in table.bodies() {
+                if states.iter().any(|(name, _, _)| name.eq_ignore_ascii_case(body)) {
+                    return Err(format!("Body '{}' appears in more than one ephemeris table", body));
+                }
+                let state = table.state_at(body, settings.epoch);
+                let position = plane.project(state.position).scale(units.length_from_si(length_m));
+                let velocity = plane.project(state.velocity).scale(units.speed_from_si(length_m / time_s));
+                states.push((body.to_string(), position, velocity));
+            }
+        }
+
+        let mut imported = Self { states };
+        if let Some(center) = &settings.center {
+            let (origin, origin_velocity) = imported.get(center).ok_or_else(|| format!("Ephemeris center '{}' is not in any table", center))?;
+            for (_, position, velocity) in &mut imported.states {
+                *position = position.subtract(&origin);
+                *velocity = velocity.subtract(&origin_velocity);
+            }
+        }
+        Ok(imported)
+    }
+}
+
+impl EphemerisTable {
+    /// Read a Horizons vector table (text or CSV output) or a CSV table with a header row
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if source.contains("$$SOE") {
+            parse_horizons(source)
+        } else {
+            parse_csv(source)
+        }
+    }
+
+    /// Names of the bodies in the table, in order of first appearance
+    pub fn bodies(&self) -> Vec<&str> {
+        let mut bodies: Vec<&str> = Vec::new();
+        for state in &self.states {
+            if !bodies.contains(&state.body.as_str()) {
+                bodies.push(&state.body);
+            }
+        }
+        bodies
+    }
+
+    /// A body's row nearest the epoch, or its first row without one
+    fn state_at(&self, body: &str, epoch: Option<f64>) -> &TableState {
+        let rows = self.states.iter().filter(|state| state.body == body);
+        match epoch {
+            Some(epoch) => rows.min_by(|a, b| (a.time - epoch).abs().total_cmp(&(b.time - epoch).abs())),
+            None => rows.min_by(|a, b| a.time.total_cmp(&b.time)),
+        }
+        .expect("bodies() only lists bodies with rows")
+    }
+}
+
+/// Split a CSV line into trimmed fields, honoring double quotes
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Columns of a vector table, found by header name
+struct Columns {
+    body: Option<usize>,
+    time: usize,
+    position: [Option<usize>; 3],
+    velocity: [Option<usize>; 3],
+}
+
+impl Columns {
+    fn from_header(header: &[String]) -> Result<Self, String> {
+        let find = |names: &[&str]| header.iter().position(|column| names.iter().any(|name| column.eq_ignore_ascii_case(name)));
+        let required = |names: &[&str]| find(names).ok_or_else(|| format!("Ephemeris table has no {} column", names[0]));
+        Ok(Self {
+            body: find(&["body", "name", "target"]),
+            time: required(&["time", "jdtdb", "jd", "epoch"])?,
+            position: [Some(required(&["x"])?), Some(required(&["y"])?), find(&["z"])],
+            velocity: [Some(required(&["vx"])?), Some(required(&["vy"])?), find(&["vz"])],
+        })
+    }
+
+    fn read(&self, fields: &[String], default_body: &str, line: usize) -> Result<TableState, String> {
+        let number = |column: Option<usize>| -> Result<f64, String> {
+            let Some(column) = column else {
+                return Ok(0.0);
+            };
+            let field = fields.get(column).map(String::as_str).unwrap_or("");
+            field.parse().map_err(|_| format!("Ephemeris line {}: '{}' is not a number", line, field))
+        };
+        let body = match self.body {
+            Some(column) => fields.get(column).cloned().unwrap_or_default(),
+            None => default_body.to_string(),
+        };
+        if body.is_empty() {
+            return Err(format!("Ephemeris line {} names no body", line));
+        }
+        Ok(TableState {
+            body,
+            time: number(Some(self.time))?,
+            position: [number(self.position[0])?, number(self.position[1])?, number(self.position[2])?],
+            velocity: [number(self.velocity[0])?, number(self.velocity[1])?, number(self.velocity[2])?],
+        })
+    }
+}
+
+/// A CSV table with a header row naming its columns, such as an exported ephemeris
+fn parse_csv(source: &str) -> Result<EphemerisTable, String> {
+    let mut lines = source.lines().enumerate().filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'));
+    let (_, header) = lines.next().ok_or("Ephemeris table is empty")?;
+    let columns = Columns::from_header(&csv_fields(header))?;
+    if columns.body.is_none() {
+        return Err("Ephemeris table has no body column".to_string());
+    }
+
+    let states = lines.map(|(i, line)| columns.read(&csv_fields(line), "", i + 1)).collect::<Result<Vec<_>, _>>()?;
+    Ok(EphemerisTable { states, length_m: None, time_s: None, plane: None })
+}
+
+/// A Horizons vector table for one target, in its text or CSV output form, with
+/// units and plane taken from its header
+fn parse_horizons(source: &str) -> Result<EphemerisTable, String> {
+    let (header, rest) = source.split_once("$$SOE").ok_or("Horizons table has no $$SOE marker")?;
+    let (data, _) = rest.split_once("$$EOE").ok_or("Horizons table has no $$EOE marker")?;
+    let data_line = header.lines().count() + 1;
+
+    let setting = |key: &str| {
+        header
+            .lines()
+            .find_map(|line| line.trim_start().strip_prefix(key).and_then(|value| value.trim_start().strip_prefix(':')))
+            .map(str::trim)
+    };
+    let target = setting("Target body name").ok_or("Horizons table has no target body name")?;
+    let target = target.split(['(', '{']).next().unwrap_or(target).trim();
+
+    let (length_m, time_s) = match setting("Output units") {
+        Some(output_units) => {
+            let (length, time) = output_units.split_once('-').ok_or_else(|| format!("Unrecognized Horizons output units '{}'", output_units))?;
+            (Some(length_unit_m(length)?), Some(time_unit_s(time)?))
+        }
+        None => (None, None),
+    };
+    let planes = [setting("Reference plane"), setting("Coordinate systm"), setting("Reference frame")];
+    let plane = if planes.iter().flatten().any(|value| value.to_ascii_lowercase().contains("ecliptic")) {
+        Some(ReferencePlane::Ecliptic)
+    } else if planes[..2].iter().flatten().any(|value| value.to_ascii_lowercase().contains("equator") || value.contains("FRAME")) {
+        Some(ReferencePlane::Equatorial)
+    } else {
+        None
+    };
+
+    // CSV output names its columns in the line just above $$SOE
+    let column_header = header.lines().rev().find(|line| !line.trim().is_empty() && !line.starts_with('*'));
+    let states = match column_header.filter(|line| line.contains(',')) {
+        Some(column_header) => {
+            let columns = Columns::from_header(&csv_fields(column_header))?;
+            data.lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| columns.read(&csv_fields(line), target, data_line + i))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        None => parse_horizons_text(data, target, data_line)?,
+    };
+    Ok(EphemerisTable { states, length_m, time_s, plane })
+}
+
+/// Horizons text output: a Julian date line, then lines of `X = value` pairs
+fn parse_horizons_text(data: &str, target: &str, first_line: usize) -> Result<Vec<TableState>, String> {
+    let mut states = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let line_number = first_line + i;
+        if line.contains("A.D.") || line.contains("B.C.") {
+            let time = line.split('=').next().unwrap_or("").trim();
+            let time = time.parse().map_err(|_| format!("Ephemeris line {}: '{}' is not a Julian date", line_number, time))?;
+            states.push(TableState { body: target.to_string(), time, position: [0.0; 3], velocity: [0.0; 3] });
+            continue;
+        }
+
+        let spaced = line.replace('=', " = ");
+        let tokens: Vec<&str> = spaced.split_whitespace().collect();
+        for pair in tokens.windows(3).filter(|pair| pair[1] == "=") {
+            let slot = match pair[0] {
+                "X" => (0, false),
+                "Y" => (1, false),
+                "Z" => (2, false),
+                "VX" => (0, true),
+                "VY" => (1, true),
+                "VZ" => (2, true),
+                _ => continue,
+            };
+            let state = states.last_mut().ok_or_else(|| format!("Ephemeris line {}: values before the first date", line_number))?;
+            let value = pair[2].parse().map_err(|_| format!("Ephemeris line {}: '{}' is not a number", line_number, pair[2]))?;
+            match slot {
+                (axis, false) => state.position[axis] = value,
+                (axis, true) => state.velocity[axis] = value,
+            }
+        }
+    }
+    Ok(states)
+}
@@ -0,0 +1,177 @@
+//! Exporting predicted ephemerides, and reading ephemeris tables: plain CSV, and
+//! JPL Horizons vector tables in their text and CSV forms. The fixtures in tests/ephemeris hold Earth's heliocentric
+//! state at J2000 as Horizons gives it in the ecliptic and in the ICRF equator.
+
+use crate::ephemeris::{Ephemeris, EphemerisOptions, EphemerisTable, ImportSettings, ImportedStates, ReferencePlane};
+use crate::game::{Game, DEFAULT_BIG_GRAVITY, TRAJECTORY_DT};
+use crate::initial_universe::calculate_stable_orbit;
+use crate::planet::Planet;
+use crate::player::Player;
+use crate::units::{UnitSystem, ASTRONOMICAL_UNIT};
+use crate::vector2::Vector2;
+
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ephemeris");
+
+// Earth at 2000-Jan-01 12:00 TDB in the ecliptic of J2000, km and km/s
+const EARTH_POSITION: Vector2 = Vector2 { x: -2.64990336774305e7, y: 1.446972967925493e8 };
+const EARTH_VELOCITY: Vector2 = Vector2 { x: -2.979426007043741e1, y: -5.469294939770602 };
+
+fn table(name: &str) -> EphemerisTable {
+    let path = format!("{}/{}", FIXTURE_DIR, name);
+    let source = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {} ({})", path, e));
+    EphemerisTable::parse(&source).expect("fixture parses")
+}
+
+fn settings() -> ImportSettings {
+    ImportSettings { epoch: None, length_m: None, time_s: None, plane: None, center: None }
+}
+
+/// Earth's imported state in km and km/s
+fn earth(table: EphemerisTable) -> (Vector2, Vector2) {
+    let units = UnitSystem::new(1000.0, 1.0, 1.0);
+    let imported = ImportedStates::from_tables(&[table], &settings(), &units).expect("table imports");
+    imported.get("earth").expect("table has Earth")
+}
+
+fn assert_near(actual: Vector2, expected: Vector2, tolerance: f64) {
+    let error = actual.subtract(&expected).magnitude();
+    assert!(error <= tolerance, "({}, {}) is {} from ({}, {})", actual.x, actual.y, error, expected.x, expected.y);
+}
+
+#[test]
+fn horizons_text_table() {
+    let table = table("earth_ecliptic.txt");
+    assert_eq!(table.bodies(), ["Earth"]);
+    assert_eq!(table.states.len(), 2);
+    assert_eq!(table.states[0].time, 2451545.0);
+    assert_eq!(table.states[0].position, [-2.64990336774305e7, 1.446972967925493e8, -6.112653160453215e2]);
+    assert_eq!(table.states[0].velocity, [-2.979426007043741e1, -5.469294939770602, 1.817836785188806e-5]);
+    assert_eq!((table.length_m, table.time_s), (Some(1000.0), Some(1.0)));
+    assert!(table.plane == Some(ReferencePlane::Ecliptic));
+
+    let (position, velocity) = earth(table);
+    assert_near(position, EARTH_POSITION, 1e-6);
+    assert_near(velocity, EARTH_VELOCITY, 1e-12);
+}
+
+#[test]
+fn horizons_csv_table_in_the_equator() {
+    let table = table("earth_equatorial.csv");
+    assert_eq!(table.bodies(), ["Earth"]);
+    assert_eq!((table.length_m, table.time_s), (Some(ASTRONOMICAL_UNIT), Some(86400.0)));
+    assert!(table.plane == Some(ReferencePlane::Equatorial));
+
+    // Tilted onto the ecliptic it agrees with Horizons' own ecliptic state, to
+    // within the difference between its frame and the mean obliquity at J2000
+    let (position, velocity) = earth(table);
+    assert_near(position, EARTH_POSITION, 1.0);
+    assert_near(velocity, EARTH_VELOCITY, 1e-5);
+}
+
+#[test]
+fn plain_csv_table() {
+    let table = table("bodies.csv");
+    assert_eq!(table.bodies(), ["Sun", "Moon, the"]);
+    assert!(table.length_m.is_none() && table.time_s.is_none() && table.plane.is_none());
+
+    // Game units by default; the epoch picks the rows and the center is brought to rest at the origin
+    let settings = ImportSettings { epoch: Some(9.0), center: Some("sun".to_string()), ..settings() };
+    let imported = ImportedStates::from_tables(&[table], &settings, &UnitSystem::game_default()).expect("table imports");
+    let (position, velocity) = imported.get("moon, the").expect("table has the Moon");
+    assert_near(position, Vector2 { x: 999.0, y: 15.0 }, 1e-9);
+    assert_near(velocity, Vector2 { x: -0.0725, y: 1.5 }, 1e-9);
+    assert_eq!(imported.get("Sun").map(|(position, _)| position.magnitude()), Some(0.0));
+}
+
+#[test]
+fn malformed_tables_are_refused() {
+    assert!(EphemerisTable::parse("time,x,y,vx,vy\n0,1,2,3,4\n").is_err()); // No body column
+    assert!(EphemerisTable::parse("body,time,x,y,vx,vy\nSun,0,1,2,three,4\n").is_err());
+    assert!(EphemerisTable::parse("Target body name: Earth (399)\n$$SOE\n2451545.0 = A.D. 2000-Jan-01\n").is_err()); // No $$EOE
+}
+
+#[test]
+fn horizons_errors_name_the_line_in_the_file() {
+    let text = "Target body name: Earth (399)\n$$SOE\n2451545.0 = A.D. 2000-Jan-01\n X = 1.0 Y = two Z = 0.0\n$$EOE\n";
+    assert_eq!(EphemerisTable::parse(text).err(), Some("Ephemeris line 4: 'two' is not a number".to_string()));
+
+    let csv = "Target body name: Earth (399)\nJDTDB, Calendar Date (TDB), X, Y, Z, VX, VY, VZ,\n$$SOE\n2451545.0, A.D. 2000-Jan-01, 1.0, two, 0.0, 0.0, 0.0, 0.0,\n$$EOE\n";
+    assert!(EphemerisTable::parse(csv).err().is_some_and(|message| message.starts_with("Ephemeris line 4:")));
+}
+
+const PREDICTION_STEPS: usize = 10;
+
+/// A star and an orbiting planet with a comma in its name, and a vessel
+/// going around the planet, predicted a few steps ahead
+fn predicted() -> Game {
+    let origin = Vector2 { x: 0.0, y: 0.0 };
+    let star = Planet::new("Star".to_string(), 300.0, 1e15, origin, origin, 0xFFFF00);
+    let (position, velocity) = calculate_stable_orbit(origin, origin, star.mass, 15000.0, 0.0, DEFAULT_BIG_GRAVITY);
+    let planet = Planet::new("Moon, the".to_string(), 150.0, 6e12, position, velocity, 0x808080);
+    let (position, velocity) = calculate_stable_orbit(planet.position, planet.velocity, planet.mass, 500.0, 0.0, DEFAULT_BIG_GRAVITY);
+    let mut game = Game::new(vec![star, planet], vec![Player::new(position, velocity, 1.0, 0.0)]);
+    game.prediction_steps = PREDICTION_STEPS;
+    game.tick = 5;
+    game.recalculate_trajectories();
+    game
+}
+
+#[test]
+fn exported_ephemeris_samples_the_prediction() {
+    let game = predicted();
+    let ephemeris = Ephemeris::from_game(&game, &EphemerisOptions::new().with_decimation(4)).expect("game has predictions");
+
+    // Every fourth step, each labeled with the time it is reached
+    let steps = [0, 4, 8];
+    assert_eq!(ephemeris.time, steps.map(|step| (game.tick + step as u64 + 1) as f64 * TRAJECTORY_DT));
+    let names: Vec<&str> = ephemeris.tracks.iter().map(|track| track.name.as_str()).collect();
+    assert_eq!(names, ["Player", "Star", "Moon, the"]);
+    let cache = &game.cached_trajectories;
+    for (i, &step) in steps.iter().enumerate() {
+        let (player, moon) = (&ephemeris.tracks[0], &ephemeris.tracks[2]);
+        assert_eq!((player.x[i], player.vy[i]), (cache.vessel_positions[0][step].x, cache.vessel_velocities[0][step].y));
+        assert_eq!((moon.y[i], moon.vx[i]), (cache.planet_positions[1][step].y, cache.planet_velocities[1][step].x));
+    }
+
+    // One CSV row per track and time, names quoted where they hold a comma
+    let csv = ephemeris.to_csv();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 1 + 3 * steps.len());
+    assert_eq!(rows[0], "time,body,x,y,vx,vy");
+    let moon = &ephemeris.tracks[2];
+    let row = format!("{},\"Moon, the\",{},{},{},{}", ephemeris.time[0], moon.x[0], moon.y[0], moon.vx[0], moon.vy[0]);
+    assert_eq!(rows[1 + 2 * steps.len()], row);
+
+    // JSON reads back to the same tables, to within serde_json's parsing of the last digit
+    let json: Ephemeris = serde_json::from_str(&ephemeris.to_json().unwrap()).expect("export is valid JSON");
+    assert!(json.frame.is_none());
+    let same = |read: &[f64], written: &[f64]| read.len() == written.len() && read.iter().zip(written).all(|(a, b)| (a - b).abs() <= 1e-15 * b.abs());
+    assert!(same(&json.time, &ephemeris.time));
+    for (read, written) in json.tracks.iter().zip(&ephemeris.tracks) {
+        assert_eq!(read.name, written.name);
+        assert!(same(&read.x, &written.x) && same(&read.y, &written.y) && same(&read.vx, &written.vx) && same(&read.vy, &written.vy));
+    }
+}
+
+#[test]
+fn ephemeris_relative_to_a_body() {
+    let game = predicted();
+    let absolute = Ephemeris::from_game(&game, &EphemerisOptions::new().with_decimation(1)).unwrap();
+    let relative = Ephemeris::from_game(&game, &EphemerisOptions::new().with_decimation(1).relative_to(Some(1))).unwrap();
+    assert_eq!(relative.frame.as_deref(), Some("Moon, the"));
+    assert_eq!(relative.time.len(), PREDICTION_STEPS);
+
+    let (player, moon) = (&absolute.tracks[0], &absolute.tracks[2]);
+    for i in 0..PREDICTION_STEPS {
+        assert_eq!((relative.tracks[2].x[i], relative.tracks[2].vy[i]), (0.0, 0.0));
+        assert_eq!(relative.tracks[0].x[i], player.x[i] - moon.x[i]);
+        assert_eq!(relative.tracks[0].vy[i], player.vy[i] - moon.vy[i]);
+    }
+}
+
+#[test]
+fn ephemeris_needs_a_prediction() {
+    let mut game = predicted();
+    game.cached_trajectories.is_valid = false;
+    assert!(Ephemeris::from_game(&game, &EphemerisOptions::new()).is_err());
+}
@@ -0,0 +1,143 @@
+//! Gravity-assist analysis of flyby arcs in a vessel's predicted trajectory
+
+use crate::game::Game;
+use crate::lagrange::primary_of;
+use crate::planet::BodyRole;
+use crate::vector2::Vector2;
+
+/// One pass through a body's sphere of influence
+#[derive(Clone, Copy)]
+pub struct Flyby {
+    pub body: usize,
+    pub periapsis_index: usize,  // Cache index of closest approach
+    pub periapsis_altitude: f64, // Above the body's surface
+    pub delta_v: f64,            // Speed gained (positive) or lost relative to the system's star
+    pub turn_angle: f64,         // Rotation of the velocity relative to the body, radians
+}
+
+/// Sphere of influence radius of a body around its primary: r = a * (m / M)^(2/5)
+pub fn sphere_of_influence(game: &Game, body: usize) -> Option<f64> {
+    let primary = primary_of(game, body)?;
+    let distance = game.planets[body].position.distance(&game.planets[primary].position);
+    Some(distance * (game.planets[body].mass / game.planets[primary].mass).powf(0.4))
+}
+
+/// Flybys found in one prediction of a vessel's path, as of the tick it was made
+pub struct FlybyCache {
+    tick: u64,
+    flybys: Vec<Flyby>,
+}
+
+/// The star a body's hierarchy leads up to, which gravity-assist gains are
+/// measured against. A system without one (e.g. planets around a binary's
+/// barycenter) uses its topmost body instead.
+fn root_star(game: &Game, body: usize) -> usize {
+    let mut root = body;
+    // Bounded in case the reference bodies form a loop
+    for _ in 0..game.planets.len() {
+        if game.planets[root].role == BodyRole::Star {
+            break;
+        }
+        match game.reference_body(root) {
+            Some(parent) => root = parent,
+            None => break,
+        }
+    }
+    root
+}
+
+/// Flybys in a vessel's cached path, with periapsis indices into the cache as it
+/// is now. Each path is scanned once per prediction and the results shifted as the
+/// game walks along it; flybys that only come into view as the prediction is
+/// extended appear once it is next rebuilt.
+pub fn predicted_flybys(game: &Game, vessel: usize) -> Vec<Flyby> {
+    if !game.cached_trajectories.is_valid {
+        return Vec::new();
+    }
+    let Some(cache) = game.cached_trajectories.flybys.get(vessel) else {
+        return find_flybys(game, vessel);
+    };
+    let cache = cache.get_or_init(|| FlybyCache { tick: game.tick, flybys: find_flybys(game, vessel) });
+    let Some(elapsed) = game.tick.checked_sub(cache.tick) else {
+        return find_flybys(game, vessel);
+    };
+    let elapsed = elapsed as usize;
+    cache
+        .flybys
+        .iter()
+        .filter(|flyby| flyby.periapsis_index >= elapsed)
+        .map(|flyby| Flyby { periapsis_index: flyby.periapsis_index - elapsed, ..*flyby })
+        .collect()
+}
+
+/// Find complete flybys in a vessel's cached path: arcs that enter a body's
+/// sphere of influence after now and leave it before the prediction ends
+pub fn find_flybys(game: &Game, vessel: usize) -> Vec<Flyby> {
+    let mut flybys = Vec::new();
+    let trajectories = &game.cached_trajectories;
+    if !trajectories.is_valid || game.vessels[vessel].landed.is_some() {
+        return flybys;
+    }
+
+    let positions = &trajectories.vessel_positions[vessel];
+    let velocities = &trajectories.vessel_velocities[vessel];
+
+    for body in 0..game.planets.len() {
+        let Some(soi) = sphere_of_influence(game, body) else {
+            continue;
+        };
+        let star = root_star(game, body);
+        let body_positions = &trajectories.planet_positions[body];
+        let inside = |idx: usize| positions[idx].distance(&body_positions[idx]) < soi;
+
+        let mut idx = 0;
+        // Skip an arc the vessel is already in
+        while idx < positions.len() && inside(idx) {
+            idx += 1;
+        }
+
+        while idx < positions.len() {
+            if !inside(idx) {
+                idx += 1;
+                continue;
+            }
+
+            let entry = idx;
+            while idx < positions.len() && inside(idx) {
+                idx += 1;
+            }
+            if idx == positions.len() {
+                break; // Still inside when the prediction ends
+            }
+            let exit = idx;
+
+            let periapsis_index = (entry..exit)
+                .min_by(|&a, &b| {
+                    let distance_a = positions[a].distance(&body_positions[a]);
+                    let distance_b = positions[b].distance(&body_positions[b]);
+                    distance_a.total_cmp(&distance_b)
+                })
+                .unwrap();
+            let periapsis_altitude = positions[periapsis_index].distance(&body_positions[periapsis_index]) - game.planets[body].radius;
+            if periapsis_altitude <= 0.0 {
+                continue; // An impact, not a flyby
+            }
+
+            // Speed change seen from the star, turn seen from the body
+            let relative_to = |planet: usize, i: usize| velocities[i].subtract(&trajectories.planet_velocities[planet][i]);
+            let delta_v = relative_to(star, exit).magnitude() - relative_to(star, entry).magnitude();
+            let (inbound, outbound): (Vector2, Vector2) = (relative_to(body, entry), relative_to(body, exit));
+            let turn_angle = inbound.cross(&outbound).atan2(inbound.dot(&outbound));
+
+            flybys.push(Flyby {
+                body,
+                periapsis_index,
+                periapsis_altitude,
+                delta_v,
+                turn_angle,
+            });
+        }
+    }
+
+    flybys
+}
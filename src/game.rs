@@ -1,109 +1,181 @@
-use crate::{planet::Planet, player::Player, vector2::Vector2};
+use crate::{flyby::FlybyCache, history::{StateHistory, HISTORY_CAPACITY}, planet::{BodyRole, Planet}, player::{Player, SurfaceContact, CRASH_SPEED_FRACTION, PLAYER_FOOTPRINT}, units::UnitSystem, vector2::Vector2};
+use std::cell::OnceCell;
 use std::collections::VecDeque;
 
+// Gravitational constant of the hand-built and generated scenarios, in game units
+pub const DEFAULT_BIG_GRAVITY: f64 = 0.000001;
+
 // Trajectory prediction constants
 const TRAJECTORY_NUM_STEPS: usize = 100000;
 pub const TRAJECTORY_DT: f64 = 0.016;
-const TRAJECTORY_SUBSTEPS: usize = 5;
+pub const TRAJECTORY_SUBSTEPS: usize = 5;
 
 pub struct Game {
     pub big_gravity: f64,
+    pub units: UnitSystem,
     pub planets: Vec<Planet>,
-    pub player: Player,
+    pub vessels: Vec<Player>,
+    pub active_vessel: usize,
+    pub tick: u64, // Number of TRAJECTORY_DT steps simulated so far
+    pub history: StateHistory,
     pub cached_trajectories: CachedTrajectories,
+    pub prediction_steps: usize, // Look-ahead of the prediction cache; zero simulates every step directly
 }
 
 pub struct CachedTrajectories {
-    pub player_positions: VecDeque<Vector2>,
-    pub player_velocities: VecDeque<Vector2>,
-    pub player_rotations: VecDeque<f64>,
+    pub vessel_positions: Vec<VecDeque<Vector2>>,
+    pub vessel_velocities: Vec<VecDeque<Vector2>>,
+    #[allow(dead_code)]
+    pub vessel_rotations: Vec<VecDeque<f64>>,
+    #[allow(dead_code)]
+    pub vessel_landed: Vec<VecDeque<Option<SurfaceContact>>>,
     pub planet_positions: Vec<VecDeque<Vector2>>,
     pub planet_velocities: Vec<VecDeque<Vector2>>,
     pub is_valid: bool,
+    pub flybys: Vec<OnceCell<FlybyCache>>, // Per vessel, found on first use and kept until the prediction is rebuilt
+}
+
+impl CachedTrajectories {
+    pub fn new() -> Self {
+        Self {
+            vessel_positions: Vec::new(),
+            vessel_velocities: Vec::new(),
+            vessel_rotations: Vec::new(),
+            vessel_landed: Vec::new(),
+            planet_positions: Vec::new(),
+            planet_velocities: Vec::new(),
+            is_valid: false,
+            flybys: Vec::new(),
+        }
+    }
 }
 
 impl Game {
-    pub fn new(planets: Vec<Planet>, player: Player) -> Self {
-        let mut game = Self {
-            big_gravity: 0.000001,
+    pub fn new(planets: Vec<Planet>, vessels: Vec<Player>) -> Self {
+        Self::with_gravity(planets, vessels, DEFAULT_BIG_GRAVITY, UnitSystem::game_default())
+    }
+
+    /// Build a game whose gravity follows from real SI constants in the given units
+    pub fn with_units(planets: Vec<Planet>, vessels: Vec<Player>, units: UnitSystem) -> Self {
+        Self::with_gravity(planets, vessels, units.gravitational_constant(), units)
+    }
+
+    /// The prediction cache starts out empty; the first frame fills it, or
+    /// recalculate_trajectories for callers that need it sooner
+    pub fn with_gravity(planets: Vec<Planet>, vessels: Vec<Player>, big_gravity: f64, units: UnitSystem) -> Self {
+        Self {
+            big_gravity,
+            units,
             planets,
-            player,
-            cached_trajectories: CachedTrajectories {
-                player_positions: VecDeque::new(),
-                player_velocities: VecDeque::new(),
-                player_rotations: VecDeque::new(),
-                planet_positions: Vec::new(),
-                planet_velocities: Vec::new(),
-                is_valid: false,
-            },
-        };
-        game.recalculate_trajectories();
-        game
+            vessels,
+            active_vessel: 0,
+            tick: 0,
+            history: StateHistory::new(HISTORY_CAPACITY),
+            cached_trajectories: CachedTrajectories::new(),
+            prediction_steps: TRAJECTORY_NUM_STEPS,
+        }
+    }
+
+    /// The vessel currently under control
+    pub fn player(&self) -> &Player {
+        &self.vessels[self.active_vessel]
+    }
+
+    pub fn player_mut(&mut self) -> &mut Player {
+        &mut self.vessels[self.active_vessel]
+    }
+
+    /// Hand control to the next vessel in the list
+    #[allow(dead_code)]
+    pub fn cycle_active_vessel(&mut self) {
+        if !self.vessels.is_empty() {
+            self.active_vessel = (self.active_vessel + 1) % self.vessels.len();
+        }
+    }
+
+    /// Add a vessel to the simulation and predict its path
+    #[allow(dead_code)]
+    pub fn spawn_vessel(&mut self, vessel: Player) {
+        self.vessels.push(vessel);
+        self.recalculate_trajectories();
     }
 
     pub fn recalculate_trajectories(&mut self) {
-        let num_steps = TRAJECTORY_NUM_STEPS;
-        let dt = TRAJECTORY_DT;
-        let substeps = TRAJECTORY_SUBSTEPS;
+        let num_steps = self.prediction_steps;
 
         // Create a copy of the game state for prediction
         let mut predicted_game = Game {
             big_gravity: self.big_gravity,
+            units: self.units,
             planets: self.planets.clone(),
-            player: self.player,
-            cached_trajectories: CachedTrajectories {
-                player_positions: VecDeque::new(),
-                player_velocities: VecDeque::new(),
-                player_rotations: VecDeque::new(),
-                planet_positions: Vec::new(),
-                planet_velocities: Vec::new(),
-                is_valid: false,
-            },
+            vessels: self.vessels.clone(),
+            active_vessel: self.active_vessel,
+            tick: self.tick,
+            history: StateHistory::new(0), // Predictions keep no history
+            cached_trajectories: CachedTrajectories::new(),
+            prediction_steps: 0,
         };
 
-        let mut player_positions = VecDeque::with_capacity(num_steps);
-        let mut player_velocities = VecDeque::with_capacity(num_steps);
-        let mut player_rotations = VecDeque::with_capacity(num_steps);
+        let mut vessel_positions: Vec<VecDeque<Vector2>> = vec![VecDeque::with_capacity(num_steps); self.vessels.len()];
+        let mut vessel_velocities: Vec<VecDeque<Vector2>> = vec![VecDeque::with_capacity(num_steps); self.vessels.len()];
+        let mut vessel_rotations: Vec<VecDeque<f64>> = vec![VecDeque::with_capacity(num_steps); self.vessels.len()];
+        let mut vessel_landed: Vec<VecDeque<Option<SurfaceContact>>> = vec![VecDeque::with_capacity(num_steps); self.vessels.len()];
         let mut planet_positions: Vec<VecDeque<Vector2>> = vec![VecDeque::with_capacity(num_steps); self.planets.len()];
         let mut planet_velocities: Vec<VecDeque<Vector2>> = vec![VecDeque::with_capacity(num_steps); self.planets.len()];
 
-        // Simulate forward and collect positions
+        // Simulate forward and collect positions, starting one step ahead of now
         for _ in 0..num_steps {
-            player_positions.push_back(predicted_game.player.position);
-            player_velocities.push_back(predicted_game.player.velocity);
-            player_rotations.push_back(predicted_game.player.rotation);
+            predicted_game.step();
+
+            for (i, vessel) in predicted_game.vessels.iter().enumerate() {
+                vessel_positions[i].push_back(vessel.position);
+                vessel_velocities[i].push_back(vessel.velocity);
+                vessel_rotations[i].push_back(vessel.rotation);
+                vessel_landed[i].push_back(vessel.landed);
+            }
 
             for (i, planet) in predicted_game.planets.iter().enumerate() {
                 planet_positions[i].push_back(planet.position);
                 planet_velocities[i].push_back(planet.velocity);
             }
-
-            // Use substeps for accurate physics
-            for _ in 0..substeps {
-                predicted_game.update(dt / substeps as f64);
-            }
         }
 
         self.cached_trajectories = CachedTrajectories {
-            player_positions,
-            player_velocities,
-            player_rotations,
+            vessel_positions,
+            vessel_velocities,
+            vessel_rotations,
+            vessel_landed,
             planet_positions,
             planet_velocities,
             is_valid: true,
+            flybys: self.vessels.iter().map(|_| OnceCell::new()).collect(),
         };
     }
 
+    #[allow(dead_code)]
     pub fn advance_trajectory(&mut self) {
-        if !self.cached_trajectories.is_valid || self.cached_trajectories.player_positions.is_empty() {
+        // Remember where we were so time can be rewound
+        let snapshot = self.snapshot();
+        self.history.push(snapshot);
+        self.tick += 1;
+
+        // Without a usable cache, simulate the step directly
+        if !self.cached_trajectories.is_valid || self.cached_trajectories.vessel_positions.iter().any(|p| p.is_empty()) {
+            self.step();
             return;
         }
 
-        // Set player to the first cached position (index 0)
+        // Set vessels to the first cached position (index 0)
         // NOTE: We DON'T set rotation here - let the player rotate freely
-        self.player.position = self.cached_trajectories.player_positions[0];
-        self.player.velocity = self.cached_trajectories.player_velocities[0];
-        // self.player.rotation = self.cached_trajectories.player_rotations[0]; // Don't overwrite rotation
+        for (i, vessel) in self.vessels.iter_mut().enumerate() {
+            vessel.position = self.cached_trajectories.vessel_positions[i][0];
+            vessel.velocity = self.cached_trajectories.vessel_velocities[i][0];
+            vessel.landed = self.cached_trajectories.vessel_landed[i][0];
+            if let Some(contact) = vessel.landed {
+                // A landed ship stays aligned with the surface normal
+                vessel.rotation = contact.rotation();
+            }
+        }
 
         // Set planet positions and velocities
         for (i, planet) in self.planets.iter_mut().enumerate() {
@@ -112,9 +184,12 @@ impl Game {
         }
 
         // Remove the positions we just used (index 0) - O(1) with VecDeque
-        self.cached_trajectories.player_positions.pop_front();
-        self.cached_trajectories.player_velocities.pop_front();
-        self.cached_trajectories.player_rotations.pop_front();
+        for i in 0..self.vessels.len() {
+            self.cached_trajectories.vessel_positions[i].pop_front();
+            self.cached_trajectories.vessel_velocities[i].pop_front();
+            self.cached_trajectories.vessel_rotations[i].pop_front();
+            self.cached_trajectories.vessel_landed[i].pop_front();
+        }
 
         for i in 0..self.planets.len() {
             self.cached_trajectories.planet_positions[i].pop_front();
@@ -122,38 +197,39 @@ impl Game {
         }
     }
 
+    #[allow(dead_code)]
     pub fn extend_trajectories(&mut self, num_steps: usize) {
         // Batch extend multiple steps at once for better performance
-        if num_steps == 0 {
+        let cache = &self.cached_trajectories;
+        if num_steps == 0 || !cache.is_valid || cache.vessel_positions.iter().chain(&cache.planet_positions).any(|p| p.is_empty()) {
             return;
         }
 
-        let dt = TRAJECTORY_DT;
-        let substeps = TRAJECTORY_SUBSTEPS;
-
-        // Get the last cached state
-        let last_idx = self.cached_trajectories.player_positions.len() - 1;
+        // Get the last cached state; every track has the same length, and there may be no planets
+        let Some(last_idx) = cache.vessel_positions.get(self.active_vessel).map(|p| p.len() - 1) else {
+            return;
+        };
 
         let mut predicted_game = Game {
             big_gravity: self.big_gravity,
+            units: self.units,
             planets: self.planets.clone(),
-            player: Player {
-                position: self.cached_trajectories.player_positions[last_idx],
-                velocity: self.cached_trajectories.player_velocities[last_idx],
-                rotation: self.cached_trajectories.player_rotations[last_idx],
-                mass: self.player.mass,
-            },
-            cached_trajectories: CachedTrajectories {
-                player_positions: VecDeque::new(),
-                player_velocities: VecDeque::new(),
-                player_rotations: VecDeque::new(),
-                planet_positions: Vec::new(),
-                planet_velocities: Vec::new(),
-                is_valid: false,
-            },
+            vessels: self.vessels.clone(),
+            active_vessel: self.active_vessel,
+            tick: self.tick,
+            history: StateHistory::new(0), // Predictions keep no history
+            cached_trajectories: CachedTrajectories::new(),
+            prediction_steps: 0,
         };
 
-        // Set planet states from last cached positions
+        // Set vessel and planet states from last cached positions
+        for i in 0..predicted_game.vessels.len() {
+            predicted_game.vessels[i].position = self.cached_trajectories.vessel_positions[i][last_idx];
+            predicted_game.vessels[i].velocity = self.cached_trajectories.vessel_velocities[i][last_idx];
+            predicted_game.vessels[i].rotation = self.cached_trajectories.vessel_rotations[i][last_idx];
+            predicted_game.vessels[i].landed = self.cached_trajectories.vessel_landed[i][last_idx];
+        }
+
         for i in 0..predicted_game.planets.len() {
             predicted_game.planets[i].position = self.cached_trajectories.planet_positions[i][last_idx];
             predicted_game.planets[i].velocity = self.cached_trajectories.planet_velocities[i][last_idx];
@@ -161,15 +237,16 @@ impl Game {
 
         // Simulate multiple steps forward
         for _ in 0..num_steps {
-            // Use substeps for accurate physics
-            for _ in 0..substeps {
-                predicted_game.update(dt / substeps as f64);
-            }
+            predicted_game.step();
 
             // Append the new state
-            self.cached_trajectories.player_positions.push_back(predicted_game.player.position);
-            self.cached_trajectories.player_velocities.push_back(predicted_game.player.velocity);
-            self.cached_trajectories.player_rotations.push_back(predicted_game.player.rotation);
+            for i in 0..predicted_game.vessels.len() {
+                let vessel = &predicted_game.vessels[i];
+                self.cached_trajectories.vessel_positions[i].push_back(vessel.position);
+                self.cached_trajectories.vessel_velocities[i].push_back(vessel.velocity);
+                self.cached_trajectories.vessel_rotations[i].push_back(vessel.rotation);
+                self.cached_trajectories.vessel_landed[i].push_back(vessel.landed);
+            }
 
             for i in 0..predicted_game.planets.len() {
                 self.cached_trajectories.planet_positions[i].push_back(predicted_game.planets[i].position);
@@ -179,7 +256,38 @@ impl Game {
     }
 
 
+    /// Advance one TRAJECTORY_DT step, using substeps for accurate physics
+    pub fn step(&mut self) {
+        for _ in 0..TRAJECTORY_SUBSTEPS {
+            self.update(TRAJECTORY_DT / TRAJECTORY_SUBSTEPS as f64);
+        }
+    }
+
+    /// Advance by `dt` with semi-implicit Euler: velocities from the current
+    /// accelerations first, then positions from the new velocities
     pub fn update(&mut self, dt: f64) {
+        let (planet_accelerations, vessel_accelerations) = self.accelerations();
+
+        // Update velocities and positions (barycenters coast at constant velocity)
+        for (i, planet) in self.planets.iter_mut().enumerate() {
+            if planet.role != BodyRole::Barycenter {
+                planet.velocity = planet.velocity.add(&planet_accelerations[i].scale(dt));
+            }
+            planet.position = planet.position.add(&planet.velocity.scale(dt));
+        }
+
+        for (v, vessel) in self.vessels.iter_mut().enumerate() {
+            if vessel.landed.is_none() {
+                vessel.velocity = vessel.velocity.add(&vessel_accelerations[v].scale(dt));
+                vessel.position = vessel.position.add(&vessel.velocity.scale(dt));
+            }
+        }
+
+        self.resolve_surface_contacts();
+    }
+
+    /// Gravitational and radiation pressure accelerations of every planet and vessel where they are now
+    pub fn accelerations(&self) -> (Vec<Vector2>, Vec<Vector2>) {
         // Calculate all accelerations for planets
         let mut planet_accelerations = vec![Vector2 { x: 0.0, y: 0.0 }; self.planets.len()];
 
@@ -203,33 +311,178 @@ impl Game {
             }
         }
 
-        // Player acceleration from planets
-        let mut player_acceleration = Vector2 { x: 0.0, y: 0.0 };
-        for (i, planet) in self.planets.iter().enumerate() {
-            let diff = planet.position.subtract(&self.player.position);
-            let distance = diff.magnitude();
-            if distance > 0.0 {
-                // F = G * m1 * m2 / r^2
-                let force_magnitude = self.big_gravity * planet.mass * self.player.mass / (distance * distance);
-                let direction = diff.scale(1.0 / distance);
+        // Vessel accelerations from planets
+        let mut vessel_accelerations = vec![Vector2 { x: 0.0, y: 0.0 }; self.vessels.len()];
+        for (v, vessel) in self.vessels.iter().enumerate() {
+            for (i, planet) in self.planets.iter().enumerate() {
+                if planet.role == BodyRole::Barycenter {
+                    continue;
+                }
+                let diff = planet.position.subtract(&vessel.position);
+                let distance = diff.magnitude();
+                if distance > 0.0 {
+                    // F = G * m1 * m2 / r^2
+                    let force_magnitude = self.big_gravity * planet.mass * vessel.mass / (distance * distance);
+                    let direction = diff.scale(1.0 / distance);
+
+                    // Vessel: a = F / m_vessel
+                    let vessel_accel = direction.scale(force_magnitude / vessel.mass);
+                    vessel_accelerations[v] = vessel_accelerations[v].add(&vessel_accel);
 
-                // Player: a = F / m_player
-                let player_accel = direction.scale(force_magnitude / self.player.mass);
-                player_acceleration = player_acceleration.add(&player_accel);
+                    // Planet: a = F / m_planet (opposite direction, Newton's 3rd law)
+                    let planet_accel = direction.scale(-force_magnitude / planet.mass);
+                    planet_accelerations[i] = planet_accelerations[i].add(&planet_accel);
+                }
 
-                // Planet: a = F / m_planet (opposite direction, Newton's 3rd law)
-                let planet_accel = direction.scale(-force_magnitude / planet.mass);
-                planet_accelerations[i] = planet_accelerations[i].add(&planet_accel);
+                // Radiation pressure on sails
+                let sail_accel = vessel.sail_acceleration(&planet.position, planet.luminosity);
+                vessel_accelerations[v] = vessel_accelerations[v].add(&sail_accel);
             }
         }
 
-        // Update velocities and positions
-        for (i, planet) in self.planets.iter_mut().enumerate() {
-            planet.velocity = planet.velocity.add(&planet_accelerations[i].scale(dt));
-            planet.position = planet.position.add(&planet.velocity.scale(dt));
+        (planet_accelerations, vessel_accelerations)
+    }
+
+    /// Keep landed vessels on their surfaces and land any that have touched one
+    pub fn resolve_surface_contacts(&mut self) {
+        for vessel in &mut self.vessels {
+            resolve_surface_contact(vessel, &self.planets, self.big_gravity);
+        }
+    }
+
+    /// FNV-1a hash over the exact bits of the simulation state, for checking
+    /// that two runs (e.g. native and wasm) agree after the same ticks
+    pub fn state_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut mix = |value: u64| {
+            for byte in value.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        };
+
+        mix(self.tick);
+        mix(self.big_gravity.to_bits());
+        mix(self.active_vessel as u64);
+
+        for planet in &self.planets {
+            mix(planet.mass.to_bits());
+            mix(planet.radius.to_bits());
+            mix(planet.position.x.to_bits());
+            mix(planet.position.y.to_bits());
+            mix(planet.velocity.x.to_bits());
+            mix(planet.velocity.y.to_bits());
+        }
+
+        for vessel in &self.vessels {
+            mix(vessel.mass.to_bits());
+            mix(vessel.rotation.to_bits());
+            mix(vessel.sail_area_to_mass.to_bits());
+            mix(vessel.position.x.to_bits());
+            mix(vessel.position.y.to_bits());
+            mix(vessel.velocity.x.to_bits());
+            mix(vessel.velocity.y.to_bits());
+            match vessel.landed {
+                Some(contact) => mix(contact.planet_index as u64),
+                None => mix(u64::MAX),
+            }
+            mix(vessel.docked.map_or(0, |docked| docked.mass.to_bits()));
+        }
+
+        hash
+    }
+
+    /// Gravitational acceleration felt by a ship resting on a planet's surface
+    pub fn surface_gravity(&self, planet_index: usize) -> f64 {
+        let planet = &self.planets[planet_index];
+        let distance = planet.radius + PLAYER_FOOTPRINT;
+        self.big_gravity * planet.mass / (distance * distance)
+    }
+
+    /// Body whose gravity pulls hardest at a position, ignoring barycenters;
+    /// falls back to the nearest barycenter in a system of massless markers
+    pub fn dominant_body(&self, position: &Vector2) -> Option<usize> {
+        self.strongest_pull(position, |_, planet| planet.role != BodyRole::Barycenter)
+            .or_else(|| self.nearest_barycenter(position, None))
+    }
+
+    /// Body that a planet orbits, chosen by role: the strongest pull among
+    /// higher-ranked bodies, else a barycenter, else a heavier body of the same
+    /// role (one half of a binary). None for the root of the system.
+    pub fn reference_body(&self, index: usize) -> Option<usize> {
+        let body = &self.planets[index];
+        let rank = body.role.rank();
+
+        self.strongest_pull(&body.position, |_, other| {
+            other.role != BodyRole::Barycenter && other.role.rank() > rank
+        })
+        .or_else(|| self.nearest_barycenter(&body.position, Some(index)))
+        .or_else(|| {
+            self.strongest_pull(&body.position, |i, other| {
+                i != index
+                    && other.role == body.role
+                    && (other.mass > body.mass || (other.mass == body.mass && i < index))
+            })
+        })
+    }
+
+    fn strongest_pull(&self, position: &Vector2, candidate: impl Fn(usize, &Planet) -> bool) -> Option<usize> {
+        let mut max_accel = 0.0;
+        let mut strongest = None;
+
+        for (i, planet) in self.planets.iter().enumerate() {
+            if !candidate(i, planet) {
+                continue;
+            }
+            let distance = planet.position.distance(position);
+            if distance > 0.0 {
+                // Gravitational acceleration: a = G * M / r^2
+                let accel = self.big_gravity * planet.mass / (distance * distance);
+                if accel > max_accel {
+                    max_accel = accel;
+                    strongest = Some(i);
+                }
+            }
         }
 
-        self.player.velocity = self.player.velocity.add(&player_acceleration.scale(dt));
-        self.player.position = self.player.position.add(&self.player.velocity.scale(dt));
+        strongest
     }
-}
\ No newline at end of file
+
+    fn nearest_barycenter(&self, position: &Vector2, exclude: Option<usize>) -> Option<usize> {
+        self.planets
+            .iter()
+            .enumerate()
+            .filter(|(i, planet)| planet.role == BodyRole::Barycenter && Some(*i) != exclude)
+            .min_by(|(_, a), (_, b)| a.position.distance(position).total_cmp(&b.position.distance(position)))
+            .map(|(i, _)| i)
+    }
+}
+
+/// Keep a landed vessel on its surface, or bring it down if it has touched one:
+/// a gentle touchdown lands, an impact faster than the crash speed wrecks it
+fn resolve_surface_contact(vessel: &mut Player, planets: &[Planet], big_gravity: f64) {
+    if vessel.landed.is_none() {
+        for (i, planet) in planets.iter().enumerate() {
+            if planet.role == BodyRole::Barycenter {
+                continue;
+            }
+            let diff = vessel.position.subtract(&planet.position);
+            let distance = diff.magnitude();
+            if distance > 0.0 && distance < planet.radius + PLAYER_FOOTPRINT {
+                let normal = diff.scale(1.0 / distance);
+                let descent_speed = -vessel.velocity.subtract(&planet.velocity).dot(&normal);
+                let crash_speed = CRASH_SPEED_FRACTION * (big_gravity * planet.mass / (planet.radius + PLAYER_FOOTPRINT)).sqrt();
+                vessel.landed = Some(SurfaceContact { planet_index: i, normal, crashed: descent_speed > crash_speed });
+                break;
+            }
+        }
+    }
+
+    // Ride along with the body at the contact point
+    if let Some(contact) = vessel.landed {
+        let planet = &planets[contact.planet_index];
+        vessel.position = planet.position.add(&contact.normal.scale(planet.radius + PLAYER_FOOTPRINT));
+        vessel.velocity = planet.velocity;
+        vessel.rotation = contact.rotation();
+    }
+}
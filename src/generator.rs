@@ -0,0 +1,289 @@
+//! Seeded procedural star-system generator. Only IEEE basic operations,
+//! sqrt and det_math are used, so a seed builds the same system on every platform.
+
+use crate::det_math;
+use crate::game::{Game, DEFAULT_BIG_GRAVITY};
+use crate::initial_universe::{calculate_elliptical_orbit, calculate_orbit};
+use crate::planet::{BodyRole, Planet};
+use crate::player::Player;
+use crate::texture::Texture;
+use crate::vector2::Vector2;
+use std::f64::consts::PI;
+
+// Neighbouring orbits are kept at least this many mutual Hill radii apart
+const HILL_SPACING: f64 = 5.0;
+
+// Moons stay inside this fraction of their planet's Hill radius
+const MOON_HILL_FRACTION: f64 = 0.45;
+
+// Densities (mass per cubic unit) of the default universe's bodies
+const STAR_DENSITY: f64 = 3.7e7;
+const PLANET_DENSITY: (f64, f64) = (1.0e6, 3.0e6);
+
+const TEXTURE_SIZE: u32 = 64;
+
+const SYLLABLES: [&str; 16] = ["ka", "lor", "ven", "tis", "mar", "do", "rin", "seth", "ul", "qua", "zen", "bri", "ot", "nax", "pe", "yra"];
+
+/// Knobs for the generator
+pub struct GeneratorSettings {
+    pub seed: u64,
+    pub planet_count: usize,
+    pub max_moons_per_planet: usize,
+    pub planet_mass_range: (f64, f64),
+    pub moon_mass_fraction: (f64, f64), // Of the parent planet's mass
+    pub stellar_mass: f64,
+}
+
+impl GeneratorSettings {
+    /// Settings on the scale of the default universe
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            planet_count: 4,
+            max_moons_per_planet: 2,
+            planet_mass_range: (5e11, 5e12),
+            moon_mass_fraction: (1e-4, 1e-2),
+            stellar_mass: 1e15,
+        }
+    }
+}
+
+/// SplitMix64, chosen for being tiny and identical everywhere
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn range(&mut self, (low, high): (f64, f64)) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+
+    fn index(&mut self, count: usize) -> usize {
+        (self.next_u64() % count as u64) as usize
+    }
+}
+
+/// Radius of a sphere of the given mass and density
+fn radius_from_density(mass: f64, density: f64) -> f64 {
+    det_math::cbrt(mass / density)
+}
+
+/// Distance between two orbits in units of their mutual Hill radius
+fn hill_separation(inner: (f64, f64), outer: (f64, f64), central_mass: f64) -> f64 {
+    let ((a_inner, m_inner), (a_outer, m_outer)) = (inner, outer);
+    let mutual_hill_radius = det_math::cbrt((m_inner + m_outer) / (3.0 * central_mass)) * (a_inner + a_outer) / 2.0;
+    (a_outer - a_inner) / mutual_hill_radius
+}
+
+/// Next orbit out from `previous`, pushed outward until it is Hill-stable
+fn next_orbit(rng: &mut Rng, previous: (f64, f64), mass: f64, central_mass: f64, spread: (f64, f64)) -> f64 {
+    let mut semi_major_axis = previous.0 * rng.range(spread);
+    while hill_separation(previous, (semi_major_axis, mass), central_mass) < HILL_SPACING {
+        semi_major_axis *= 1.05;
+    }
+    semi_major_axis
+}
+
+fn generate_name(rng: &mut Rng) -> String {
+    let syllables = 2 + rng.index(2);
+    let name: String = (0..syllables).map(|_| SYLLABLES[rng.index(SYLLABLES.len())]).collect();
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// Saturated color from a hue in [0, 1)
+fn hue_color(hue: f64, saturation: f64, value: f64) -> u32 {
+    let sector = hue * 6.0;
+    let i = sector as u32 % 6;
+    let f = sector - sector.floor();
+    let (p, q, t) = (value * (1.0 - saturation), value * (1.0 - saturation * f), value * (1.0 - saturation * (1.0 - f)));
+    let (r, g, b) = match i {
+        0 => (value, t, p),
+        1 => (q, value, p),
+        2 => (p, value, t),
+        3 => (p, q, value),
+        4 => (t, p, value),
+        _ => (value, p, q),
+    };
+    ((r * 255.0) as u32) << 16 | ((g * 255.0) as u32) << 8 | (b * 255.0) as u32
+}
+
+/// Smooth value noise over an integer lattice hashed from the seed
+fn value_noise(seed: u64, x: f64, y: f64) -> f64 {
+    let lattice = |ix: i64, iy: i64| {
+        let mut h = seed ^ (ix as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (iy as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        h = (h ^ (h >> 29)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        (h >> 11) as f64 / (1u64 << 53) as f64
+    };
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (sx, sy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+    let (ix, iy) = (x0 as i64, y0 as i64);
+
+    let top = lattice(ix, iy) + (lattice(ix + 1, iy) - lattice(ix, iy)) * sx;
+    let bottom = lattice(ix, iy + 1) + (lattice(ix + 1, iy + 1) - lattice(ix, iy + 1)) * sx;
+    top + (bottom - top) * sy
+}
+
+/// Give a body its procedural texture, recorded by the parameters that rebuild it
+fn with_generated_texture(planet: Planet, seed: u64, color: u32, banded: bool) -> Planet {
+    planet
+        .with_texture(generate_texture(seed, color, banded))
+        .with_texture_source(format!("generated:{}:{:06x}:{}", seed, color, banded))
+}
+
+/// Rebuild a texture from the source string `with_generated_texture` recorded
+pub fn generated_texture(source: &str) -> Option<Texture> {
+    let mut parts = source.strip_prefix("generated:")?.split(':');
+    let seed = parts.next()?.parse().ok()?;
+    let color = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let banded = parts.next()?.parse().ok()?;
+    Some(generate_texture(seed, color, banded))
+}
+
+/// Procedural surface: mottled for rocky bodies, banded for giants and stars
+fn generate_texture(seed: u64, color: u32, banded: bool) -> Texture {
+    let mut pixels = Vec::with_capacity((TEXTURE_SIZE * TEXTURE_SIZE) as usize);
+    for y in 0..TEXTURE_SIZE {
+        for x in 0..TEXTURE_SIZE {
+            let (u, v) = (x as f64 / TEXTURE_SIZE as f64, y as f64 / TEXTURE_SIZE as f64);
+            let detail = 0.65 * value_noise(seed, u * 6.0, v * 6.0) + 0.35 * value_noise(seed ^ 1, u * 16.0, v * 16.0);
+            let shade = if banded {
+                0.75 + 0.25 * det_math::sin(v * 9.0 * PI + detail * 3.0)
+            } else {
+                0.55 + 0.6 * detail
+            };
+
+            let channel = |shift: u32| ((((color >> shift) & 0xFF) as f64 * shade).clamp(0.0, 255.0) as u32) << shift;
+            pixels.push(0xFF00_0000 | channel(16) | channel(8) | channel(0));
+        }
+    }
+    Texture { width: TEXTURE_SIZE, height: TEXTURE_SIZE, pixels }
+}
+
+fn describe_planet(name: &str, mass: f64, settings: &GeneratorSettings, moons: usize, order: usize) -> String {
+    let (low, high) = settings.planet_mass_range;
+    let kind = if mass > low + 0.66 * (high - low) {
+        "A massive banded giant"
+    } else if mass > low + 0.33 * (high - low) {
+        "A mid-sized world"
+    } else {
+        "A small rocky world"
+    };
+    let moons = match moons {
+        0 => "no moons".to_string(),
+        1 => "one moon".to_string(),
+        n => format!("{} moons", n),
+    };
+    format!("{}, planet {} from its star, with {}. {} was generated procedurally.", kind, order + 1, moons, name)
+}
+
+/// Build a star system from a seed, with every body on a Hill-stable orbit
+pub fn generate_system(settings: &GeneratorSettings) -> Game {
+    let mut rng = Rng(settings.seed);
+
+    // The star
+    let star_name = generate_name(&mut rng);
+    let star_mass = settings.stellar_mass;
+    let star_radius = radius_from_density(star_mass, STAR_DENSITY);
+    let star_color = hue_color(rng.range((0.05, 0.17)), 0.5, 1.0);
+    let relative_mass = star_mass / 1e15;
+    let star = Planet::new(star_name.clone(), star_radius, star_mass, Vector2 { x: 0.0, y: 0.0 }, Vector2 { x: 0.0, y: 0.0 }, star_color);
+    let star = with_generated_texture(star, rng.next_u64(), star_color, true)
+        .with_description(format!("{}, a star of {:.2} solar masses at the center of a generated system.", star_name, relative_mass))
+        .with_luminosity(1e7 * relative_mass * relative_mass * relative_mass)
+        .with_role(BodyRole::Star);
+    let (star_position, star_velocity) = (star.position, star.velocity);
+    let mut planets = vec![star];
+
+    let mut previous: Option<(f64, f64)> = None;
+    let mut vessel_parent = None;
+    for order in 0..settings.planet_count {
+        let mass = rng.range(settings.planet_mass_range);
+        let density = rng.range(PLANET_DENSITY);
+        let radius = radius_from_density(mass, density);
+
+        let semi_major_axis = match previous {
+            None => star_radius * rng.range((30.0, 50.0)),
+            Some(previous) => next_orbit(&mut rng, previous, mass, star_mass, (1.4, 2.0)),
+        };
+        previous = Some((semi_major_axis, mass));
+
+        let eccentricity = rng.range((0.0, 0.05));
+        let angle = rng.range((0.0, 2.0 * PI));
+        let (position, velocity) = calculate_elliptical_orbit(star_position, star_velocity, star_mass, semi_major_axis, eccentricity, angle, DEFAULT_BIG_GRAVITY);
+
+        let name = generate_name(&mut rng);
+        let color = hue_color(rng.next_f64(), rng.range((0.3, 0.8)), rng.range((0.6, 1.0)));
+        let banded = mass > (settings.planet_mass_range.0 + settings.planet_mass_range.1) / 2.0;
+        let texture_seed = rng.next_u64();
+
+        // Moons between just outside the Roche zone and the stable part of the Hill sphere
+        let hill_radius = semi_major_axis * det_math::cbrt(mass / (3.0 * star_mass));
+        let moon_count = rng.index(settings.max_moons_per_planet + 1);
+        let mut moons = Vec::new();
+        let mut previous_moon: Option<(f64, f64)> = None;
+        for _ in 0..moon_count {
+            let moon_mass = mass * rng.range(settings.moon_mass_fraction);
+            // At most four times the planet's density keeps the Roche limit under two planet radii
+            let moon_density = density * rng.range((0.5, 4.0));
+            let orbit = match previous_moon {
+                None => radius * rng.range((3.0, 5.0)),
+                Some(previous_moon) => next_orbit(&mut rng, previous_moon, moon_mass, mass, (1.3, 1.8)),
+            };
+            if orbit > MOON_HILL_FRACTION * hill_radius {
+                break;
+            }
+            previous_moon = Some((orbit, moon_mass));
+
+            let (moon_position, moon_velocity) = calculate_orbit(position, velocity, mass, orbit, 0.0, rng.range((0.0, 2.0 * PI)), DEFAULT_BIG_GRAVITY);
+            let moon_name = generate_name(&mut rng);
+            let moon_color = hue_color(rng.next_f64(), 0.15, rng.range((0.5, 0.9)));
+            let moon = Planet::new(moon_name.clone(), radius_from_density(moon_mass, moon_density), moon_mass, moon_position, moon_velocity, moon_color);
+            moons.push(
+                with_generated_texture(moon, rng.next_u64(), moon_color, false)
+                    .with_description(format!("{}, a generated moon of {}.", moon_name, name))
+                    .with_role(BodyRole::Moon),
+            );
+        }
+
+        // The player starts around the first planet
+        if vessel_parent.is_none() {
+            vessel_parent = Some((position, velocity, mass, radius));
+        }
+
+        let planet = Planet::new(name.clone(), radius, mass, position, velocity, color);
+        planets.push(
+            with_generated_texture(planet, texture_seed, color, banded)
+                .with_description(describe_planet(&name, mass, settings, moons.len(), order)),
+        );
+        planets.extend(moons);
+    }
+
+    let vessel = match vessel_parent {
+        Some((position, velocity, mass, radius)) => {
+            let (position, velocity) = calculate_orbit(position, velocity, mass, 2.0 * radius, 0.0, 0.0, DEFAULT_BIG_GRAVITY);
+            Player::new(position, velocity, 1.0, 0.0)
+        }
+        None => {
+            let (position, velocity) = calculate_orbit(star_position, star_velocity, star_mass, 10.0 * star_radius, 0.0, 0.0, DEFAULT_BIG_GRAVITY);
+            Player::new(position, velocity, 1.0, 0.0)
+        }
+    };
+
+    Game::new(planets, vec![vessel])
+}
@@ -0,0 +1,155 @@
+//! Ghost runs: a recorded flight simulated ahead of time and shown beside the
+//! live player, synchronized by sim time, to race against
+
+use crate::flyby::sphere_of_influence;
+use crate::game::{Game, TRAJECTORY_DT};
+use crate::replay::{Playback, Recording};
+use crate::save::ViewState;
+use crate::vector2::Vector2;
+
+// Arrival distance around a body with no sphere of influence (the root of the system), in body radii
+#[allow(dead_code)]
+const ROOT_OBJECTIVE_RADII: f64 = 10.0;
+
+/// Where the ghost was at the end of one recorded frame
+pub struct GhostSample {
+    pub tick: u64,
+    pub position: Vector2,
+    pub rotation: f64,
+    pub reference: Option<usize>, // Body pulling hardest on the ghost
+    planet_positions: Vec<Vector2>,
+}
+
+impl GhostSample {
+    #[allow(dead_code)]
+    fn capture(game: &Game) -> Self {
+        let player = game.player();
+        Self {
+            tick: game.tick,
+            position: player.position,
+            rotation: player.rotation,
+            reference: game.dominant_body(&player.position),
+            planet_positions: game.planets.iter().map(|planet| planet.position).collect(),
+        }
+    }
+
+    /// Position to draw now: as recorded, or carried along with a body so the
+    /// path reads like a trajectory relative to it
+    pub fn position_relative_to(&self, game: &Game, body: Option<usize>) -> Vector2 {
+        match body {
+            Some(body) if body < game.planets.len() => {
+                self.position.subtract(&self.planet_positions[body]).add(&game.planets[body].position)
+            }
+            _ => self.position,
+        }
+    }
+}
+
+pub struct Ghost {
+    samples: Vec<GhostSample>, // Increasing tick order
+    pub objective: usize,
+    #[allow(dead_code)]
+    reach: f64, // Distance from the objective that counts as arriving
+    ghost_arrival: Option<u64>,
+    player_arrival: Option<u64>,
+    #[allow(dead_code)]
+    player_outside: bool, // Whether the player was outside the reach last frame
+}
+
+impl Ghost {
+    /// Fly a recording headless to build its ghost, and return the game as the
+    /// recording starts so the live attempt races from the same state. The
+    /// objective defaults to the body the ghost ended up near.
+    #[allow(dead_code)]
+    pub fn race(recording: Recording, objective: Option<usize>) -> Result<(Self, Game, ViewState), String> {
+        let (mut playback, mut ghost_game, _) = Playback::start(recording)?;
+        let (game, view) = playback.start_state()?;
+
+        // Only the ghost's positions are needed, so skip the prediction cache
+        ghost_game.prediction_steps = 0;
+        ghost_game.cached_trajectories.is_valid = false;
+
+        let mut samples = vec![GhostSample::capture(&ghost_game)];
+        while playback.step(&mut ghost_game) {
+            // Rewinding during the recording abandons the branch it rewound
+            while samples.last().is_some_and(|last| last.tick >= ghost_game.tick) {
+                samples.pop();
+            }
+            samples.push(GhostSample::capture(&ghost_game));
+        }
+
+        let objective = objective
+            .filter(|&body| body < game.planets.len())
+            .or_else(|| samples.last().and_then(|sample| sample.reference))
+            .ok_or("Recording has no body to race to")?;
+        let reach = sphere_of_influence(&game, objective).unwrap_or(game.planets[objective].radius * ROOT_OBJECTIVE_RADII);
+        // Arriving means crossing into the reach, so a flight starting at the objective has to leave and come back
+        let inside = |sample: &GhostSample| sample.planet_positions.get(objective).is_some_and(|body| sample.position.distance(body) < reach);
+        let ghost_arrival = samples.windows(2).find(|pair| !inside(&pair[0]) && inside(&pair[1])).map(|pair| pair[1].tick);
+
+        let player_outside = game.player().position.distance(&game.planets[objective].position) >= reach;
+        let ghost = Self { samples, objective, reach, ghost_arrival, player_arrival: None, player_outside };
+        Ok((ghost, game, view))
+    }
+
+    pub fn samples(&self) -> &[GhostSample] {
+        &self.samples
+    }
+
+    /// The ghost at a sim tick: its latest sample at or before it, or None
+    /// before the recording started
+    pub fn sample_at(&self, tick: u64) -> Option<&GhostSample> {
+        let index = self.samples.partition_point(|sample| sample.tick <= tick);
+        index.checked_sub(1).map(|i| &self.samples[i])
+    }
+
+    /// Note when the live player reaches the objective, forgetting it again if time is rewound past it
+    #[allow(dead_code)]
+    pub fn update(&mut self, game: &Game) {
+        if self.player_arrival.is_some_and(|tick| tick > game.tick) {
+            self.player_arrival = None;
+        }
+        let Some(objective) = game.planets.get(self.objective) else {
+            return;
+        };
+        let inside = game.player().position.distance(&objective.position) < self.reach;
+        if inside && self.player_outside && self.player_arrival.is_none() {
+            self.player_arrival = Some(game.tick);
+        }
+        self.player_outside = !inside;
+    }
+
+    /// HUD line comparing the live player with the ghost
+    pub fn status(&self, game: &Game) -> String {
+        let Some(objective) = game.planets.get(self.objective) else {
+            return "Ghost: objective no longer exists".to_string();
+        };
+        let seconds = |ticks: u64| ticks as f64 * TRAJECTORY_DT;
+        let ahead_or_behind = |ahead: bool| if ahead { "ahead of" } else { "behind" };
+
+        match (self.player_arrival, self.ghost_arrival) {
+            (Some(player), Some(ghost)) => {
+                format!("Ghost: reached {} {:.1} s {} the ghost", objective.name, seconds(player.abs_diff(ghost)), ahead_or_behind(player <= ghost))
+            }
+            (Some(_), None) => format!("Ghost: reached {}, the ghost never did", objective.name),
+            (None, Some(ghost)) if game.tick >= ghost => {
+                format!("Ghost: reached {} {:.1} s ago, you are behind", objective.name, seconds(game.tick - ghost))
+            }
+            _ => {
+                // Neither has arrived: compare how far each still has to go
+                let Some(sample) = self.sample_at(game.tick) else {
+                    return format!("Ghost: race to {} not started", objective.name);
+                };
+                let player_distance = game.player().position.distance(&objective.position);
+                let ghost_distance = sample.position.distance(&objective.position);
+                format!(
+                    "Ghost: {} you {} ghost {} ({} the ghost)",
+                    objective.name,
+                    game.units.format_length(player_distance),
+                    game.units.format_length(ghost_distance),
+                    ahead_or_behind(player_distance <= ghost_distance),
+                )
+            }
+        }
+    }
+}
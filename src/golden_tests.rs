@@ -0,0 +1,119 @@
+//! Golden-image regression tests for the renderer. Each scene is rendered offscreen
+//! and must match tests/golden/<name>.png pixel for pixel. After an intended change
+//! to how things are drawn, run `UPDATE_GOLDEN=1 cargo test golden` to rewrite the
+//! images, then look over the new ones before committing them.
+
+use crate::ephemeris::body_by_name;
+use crate::game::Game;
+use crate::initial_universe::scenario_by_name;
+use crate::offscreen::render_image;
+use crate::render::{Camera, TrajectoryFrame};
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+// Where a mismatching render is written for comparison
+const ACTUAL_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/golden");
+
+const WIDTH: usize = 640;
+const HEIGHT: usize = 480;
+
+/// A built-in scenario with its predictions made, and without its image-file
+/// textures so renders don't depend on whether the resource files are checked
+/// out. Generated textures are kept.
+fn scenario(name: &str) -> Game {
+    let mut game = scenario_by_name(name).expect("built-in scenario exists");
+    for planet in &mut game.planets {
+        if !planet.texture_source.as_deref().is_some_and(|source| source.starts_with("generated:")) {
+            planet.texture = None;
+        }
+    }
+    game.recalculate_trajectories();
+    game
+}
+
+fn body(game: &Game, name: &str) -> usize {
+    body_by_name(game, name).expect("scenario has the body")
+}
+
+fn assert_golden(name: &str, game: &Game, camera: &Camera) {
+    let actual = render_image(game, camera).expect("camera fits the game");
+    let path = format!("{}/{}.png", GOLDEN_DIR, name);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+        actual.save(&path).unwrap();
+        return;
+    }
+
+    let expected = image::open(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {} ({}); run with UPDATE_GOLDEN=1 to create it", path, e))
+        .to_rgb8();
+    let differing = if expected.dimensions() == actual.dimensions() {
+        expected.pixels().zip(actual.pixels()).filter(|(a, b)| a != b).count()
+    } else {
+        expected.pixels().len().max(actual.pixels().len())
+    };
+    if differing > 0 {
+        std::fs::create_dir_all(ACTUAL_DIR).unwrap();
+        let actual_path = format!("{}/{}.png", ACTUAL_DIR, name);
+        actual.save(&actual_path).unwrap();
+        panic!("{} differs from {} in {} pixels; the new render is at {}", name, path, differing, actual_path);
+    }
+}
+
+#[test]
+fn golden_hud() {
+    let game = scenario("default");
+    let camera = Camera::new(WIDTH, HEIGHT).with_time_warp(4.0).with_thrust(true).with_deterministic(true);
+    assert_golden("hud", &game, &camera);
+}
+
+#[test]
+fn golden_trajectories_relative() {
+    let game = scenario("default");
+    assert_golden("trajectories_relative", &game, &Camera::new(WIDTH, HEIGHT).with_zoom(0.01));
+}
+
+#[test]
+fn golden_trajectories_absolute() {
+    let game = scenario("default");
+    let camera = Camera::new(WIDTH, HEIGHT).with_zoom(0.01).with_frame(TrajectoryFrame::Absolute);
+    assert_golden("trajectories_absolute", &game, &camera);
+}
+
+#[test]
+fn golden_trajectories_rotating() {
+    let game = scenario("default");
+    let (earth, moon) = (body(&game, "Earth"), body(&game, "Moon"));
+    let camera = Camera::new(WIDTH, HEIGHT)
+        .with_zoom(0.1)
+        .with_frame(TrajectoryFrame::Rotating(earth, moon))
+        .with_lagrange_pair(earth, moon);
+    assert_golden("trajectories_rotating", &game, &camera);
+}
+
+#[test]
+fn golden_trajectories_pinned() {
+    let game = scenario("default");
+    let camera = Camera::new(WIDTH, HEIGHT).with_zoom(0.01).with_frame(TrajectoryFrame::Body(body(&game, "Moon")));
+    assert_golden("trajectories_pinned", &game, &camera);
+}
+
+#[test]
+fn golden_planet_sprites() {
+    let game = scenario("seed:7");
+    assert_golden("planet_sprites", &game, &Camera::new(WIDTH, HEIGHT));
+}
+
+#[test]
+fn golden_info_panel() {
+    let game = scenario("default");
+    let camera = Camera::new(WIDTH, HEIGHT).with_zoom(0.01).with_selected_planet(body(&game, "Earth"));
+    assert_golden("info_panel", &game, &camera);
+}
+
+#[test]
+fn golden_info_panel_texture() {
+    let game = scenario("seed:7");
+    let camera = Camera::new(WIDTH, HEIGHT).with_zoom(0.01).with_selected_planet(1);
+    assert_golden("info_panel_texture", &game, &camera);
+}
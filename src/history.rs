@@ -0,0 +1,86 @@
+//! Bounded history of recent simulation states, used to rewind time
+
+use crate::game::Game;
+use crate::player::Player;
+use crate::vector2::Vector2;
+use std::collections::VecDeque;
+
+// Number of TRAJECTORY_DT steps kept (about 5 minutes of sim time)
+pub const HISTORY_CAPACITY: usize = 20000;
+
+/// Everything that changes while the simulation runs
+#[derive(Clone)]
+#[allow(dead_code)]
+pub struct Snapshot {
+    pub tick: u64,
+    pub planet_states: Vec<(Vector2, Vector2)>, // (position, velocity)
+    pub vessels: Vec<Player>,
+    pub active_vessel: usize,
+}
+
+pub struct StateHistory {
+    snapshots: VecDeque<Snapshot>,
+    #[allow(dead_code)]
+    capacity: usize,
+}
+
+impl StateHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    #[allow(dead_code)]
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+impl Game {
+    #[allow(dead_code)]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            tick: self.tick,
+            planet_states: self.planets.iter().map(|planet| (planet.position, planet.velocity)).collect(),
+            vessels: self.vessels.clone(),
+            active_vessel: self.active_vessel,
+        }
+    }
+
+    /// Step one tick back in time. The prediction cache is invalidated, so the
+    /// caller should recalculate trajectories once rewinding stops; anything
+    /// done from here on branches a new timeline.
+    #[allow(dead_code)]
+    pub fn rewind_step(&mut self) -> bool {
+        let Some(snapshot) = self.history.pop() else {
+            return false;
+        };
+
+        self.tick = snapshot.tick;
+        for (planet, (position, velocity)) in self.planets.iter_mut().zip(snapshot.planet_states) {
+            planet.position = position;
+            planet.velocity = velocity;
+        }
+        self.vessels = snapshot.vessels;
+        self.active_vessel = snapshot.active_vessel;
+        self.cached_trajectories.is_valid = false;
+        true
+    }
+}
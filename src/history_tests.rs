@@ -0,0 +1,82 @@
+//! Rewinding through the state history, and branching a new timeline from it.
+
+use crate::game::{Game, DEFAULT_BIG_GRAVITY, TRAJECTORY_DT};
+use crate::history::StateHistory;
+use crate::initial_universe::calculate_stable_orbit;
+use crate::keyboard_input::InputState;
+use crate::planet::Planet;
+use crate::player::Player;
+use crate::vector2::Vector2;
+
+const TICKS: usize = 20;
+
+/// A vessel in orbit around a planet, with a short look-ahead
+fn orbit() -> Game {
+    let origin = Vector2 { x: 0.0, y: 0.0 };
+    let planet = Planet::new("Planet".to_string(), 100.0, 1e12, origin, origin, 0x4040FF);
+    let (position, velocity) = calculate_stable_orbit(origin, origin, planet.mass, 500.0, 0.0, DEFAULT_BIG_GRAVITY);
+    let mut game = Game::new(vec![planet], vec![Player::new(position, velocity, 1.0, 0.0)]);
+    game.prediction_steps = 100;
+    game
+}
+
+/// Run one tick per frame with the given controls, returning the state hash after each
+fn fly(game: &mut Game, input: &InputState, frames: usize) -> Vec<u64> {
+    let mut time_accumulator = 0.0;
+    (0..frames)
+        .map(|_| {
+            input.advance_frame(game, &mut time_accumulator, TRAJECTORY_DT, 1.0, true);
+            game.state_hash()
+        })
+        .collect()
+}
+
+#[test]
+fn rewinding_returns_to_earlier_states() {
+    let mut game = orbit();
+    let start = game.state_hash();
+    let hashes = fly(&mut game, &InputState::new(), TICKS);
+
+    let earlier = std::iter::once(start).chain(hashes.iter().copied()).rev().skip(1);
+    for (tick, hash) in (0..TICKS).rev().zip(earlier) {
+        assert!(game.rewind_step());
+        assert_eq!((game.tick, game.state_hash()), (tick as u64, hash));
+    }
+    assert!(!game.rewind_step());
+}
+
+#[test]
+fn thrusting_after_a_rewind_branches_the_timeline() {
+    let mut game = orbit();
+    let coasting = fly(&mut game, &InputState::new(), TICKS);
+
+    let mut rewind = InputState::new();
+    rewind.rewind = true;
+    fly(&mut game, &rewind, TICKS / 2);
+    assert_eq!(game.state_hash(), coasting[TICKS / 2 - 1]);
+
+    let mut thrust = InputState::new();
+    thrust.thrust = true;
+    let branched = fly(&mut game, &thrust, TICKS / 2);
+    assert_eq!(game.tick, TICKS as u64);
+    assert!(branched.iter().zip(&coasting[TICKS / 2..]).all(|(a, b)| a != b));
+
+    // The history now holds the new branch
+    fly(&mut game, &InputState::new(), 1);
+    fly(&mut game, &rewind, 1);
+    assert_eq!(game.state_hash(), branched[TICKS / 2 - 1]);
+}
+
+#[test]
+fn history_keeps_at_most_its_capacity() {
+    let game = orbit();
+    let mut none = StateHistory::new(0);
+    none.push(game.snapshot());
+    assert!(none.pop().is_none());
+
+    let mut two = StateHistory::new(2);
+    for _ in 0..3 {
+        two.push(game.snapshot());
+    }
+    assert!(two.pop().is_some() && two.pop().is_some() && two.pop().is_none());
+}
@@ -1,10 +1,14 @@
-use crate::game::Game;
-use crate::planet::Planet;
+use crate::game::{Game, DEFAULT_BIG_GRAVITY};
+use crate::planet::{BodyRole, Planet};
 use crate::player::Player;
 use crate::vector2::Vector2;
 use crate::texture::Texture;
+use crate::scenario::load_scenario;
+use crate::generator::{generate_system, GeneratorSettings};
+use crate::units::{UnitSystem, SOLAR_LUMINOSITY};
 
-// Embed planet textures at compile time
+// Embed the default scenario and its textures at compile time
+const DEFAULT_SCENARIO: &str = include_str!("../scenarios/default.toml");
 const BEN_TEXTURE_BYTES: &[u8] = include_bytes!("../resources/ben.png");
 const EARTH_TEXTURE_BYTES: &[u8] = include_bytes!("../resources/earth.png");
 const MARTY_TEXTURE_BYTES: &[u8] = include_bytes!("../resources/marty.png");
@@ -13,7 +17,7 @@ const PUCKMAN_TEXTURE_BYTES: &[u8] = include_bytes!("../resources/puckman.png");
 
 /// Calculate stable orbital position and velocity around a center body
 /// Returns (position, velocity) relative to the center body
-fn calculate_stable_orbit(
+pub fn calculate_stable_orbit(
     center_position: Vector2,
     center_velocity: Vector2,
     center_mass: f64,
@@ -21,228 +25,295 @@ fn calculate_stable_orbit(
     eccentricity: f64,
     big_gravity: f64,
 ) -> (Vector2, Vector2) {
-    // For circular orbit (e=0): v = sqrt(G * M / r)
-    // For elliptical orbit at periapsis: v = sqrt(G * M * (1+e) / (r * (1-e)))
+    calculate_orbit(center_position, center_velocity, center_mass, radius, eccentricity, 0.0, big_gravity)
+}
 
-    let orbital_speed = if eccentricity == 0.0 {
-        (big_gravity * center_mass / radius).sqrt()
-    } else {
-        (big_gravity * center_mass * (1.0 + eccentricity) / (radius * (1.0 - eccentricity))).sqrt()
-    };
+/// Like `calculate_stable_orbit`, with periapsis turned by `argument_of_periapsis`
+/// radians counterclockwise from the +x axis
+pub fn calculate_orbit(
+    center_position: Vector2,
+    center_velocity: Vector2,
+    center_mass: f64,
+    radius: f64,
+    eccentricity: f64,
+    argument_of_periapsis: f64,
+    big_gravity: f64,
+) -> (Vector2, Vector2) {
+    // Speed at periapsis r_p: v = sqrt(G * M * (1+e) / r_p), which is sqrt(G * M / r) for a circular orbit
+    let orbital_speed = (big_gravity * center_mass * (1.0 + eccentricity) / radius).sqrt();
 
     // Position: start at the specified radius (periapsis for elliptical orbits)
-    let position = Vector2 {
-        x: center_position.x + radius,
-        y: center_position.y,
-    };
+    let offset = Vector2 { x: radius, y: 0.0 }.rotate(argument_of_periapsis);
+    let position = center_position.add(&offset);
 
     // Velocity: perpendicular to radius vector, plus center body's velocity
-    let velocity = Vector2 {
-        x: center_velocity.x,
-        y: center_velocity.y + orbital_speed,
-    };
+    let velocity = center_velocity.add(&Vector2 { x: 0.0, y: orbital_speed }.rotate(argument_of_periapsis));
 
     (position, velocity)
 }
 
+/// Like `calculate_orbit`, for an orbit sized by its semi-major axis rather than its periapsis
+pub fn calculate_elliptical_orbit(
+    center_position: Vector2,
+    center_velocity: Vector2,
+    center_mass: f64,
+    semi_major_axis: f64,
+    eccentricity: f64,
+    argument_of_periapsis: f64,
+    big_gravity: f64,
+) -> (Vector2, Vector2) {
+    let periapsis = semi_major_axis * (1.0 - eccentricity);
+    calculate_orbit(center_position, center_velocity, center_mass, periapsis, eccentricity, argument_of_periapsis, big_gravity)
+}
+
+/// The default universe, built from the scenario file shipped with the game
 pub fn create_universe() -> Game {
-    let big_gravity = 0.000001;
+    let no_tables = |path: &str| Err(format!("no embedded ephemeris table {}", path));
+    load_scenario(DEFAULT_SCENARIO, embedded_texture, no_tables).expect("built-in default scenario is valid")
+}
+
+/// Resolve the default scenario's texture paths to the embedded images,
+/// falling back to plain colors if an image fails to decode
+pub fn embedded_texture(path: &str) -> Result<Option<Texture>, String> {
+    let bytes = match path {
+        "../resources/ben.png" => BEN_TEXTURE_BYTES,
+        "../resources/earth.png" => EARTH_TEXTURE_BYTES,
+        "../resources/marty.png" => MARTY_TEXTURE_BYTES,
+        "../resources/shirley.png" => SHIRLEY_TEXTURE_BYTES,
+        "../resources/puckman.png" => PUCKMAN_TEXTURE_BYTES,
+        _ => return Err(format!("no embedded texture for {}", path)),
+    };
+    Ok(Texture::load_from_bytes(bytes).ok())
+}
+
+/// Build a scenario by name, e.g. from a command-line argument
+pub fn scenario_by_name(name: &str) -> Option<Game> {
+    match name {
+        "default" => Some(create_universe()),
+        "solar-system" => Some(create_solar_system()),
+        "binary-star" => Some(create_binary_star()),
+        "rogue-planets" => Some(create_rogue_planets()),
+        _ => {
+            // "seed:<n>" builds a procedurally generated system
+            let seed = name.strip_prefix("seed:")?.parse().ok()?;
+            Some(generate_system(&GeneratorSettings::with_seed(seed)))
+        }
+    }
+}
+
+/// True-scale solar system with SI masses, radii and circular orbits
+pub fn create_solar_system() -> Game {
+    // 1 unit = 10 km, 1 mass unit = 1e20 kg, 1 game second = 100 s
+    let units = UnitSystem::new(1.0e4, 1.0e20, 100.0);
+    let big_gravity = units.gravitational_constant();
 
-    // Sun at origin
     let sun_position = Vector2 { x: 0.0, y: 0.0 };
     let sun_velocity = Vector2 { x: 0.0, y: 0.0 };
-    let sun_mass = 1e15;
-
-    // Earth orbiting Sun
-    let earth_orbit_radius = 15000.0;
-    let earth_mass = 6e12;
-    let (earth_position, earth_velocity) = calculate_stable_orbit(
-        sun_position,
-        sun_velocity,
-        sun_mass,
-        earth_orbit_radius,
+    let sun_mass = units.mass_from_si(1.989e30);
+
+    let mut planets = vec![
+        Planet::new(
+            "Sun".to_string(),
+            units.length_from_si(6.957e8),
+            sun_mass,
+            sun_position,
+            sun_velocity,
+            0xFFFF00 // yellow
+        )
+        .with_description("The Sun at its real size and mass.".to_string())
+        .with_luminosity(units.luminosity_from_si(SOLAR_LUMINOSITY))
+        .with_role(BodyRole::Star),
+    ];
+
+    // (name, mass kg, radius m, orbit radius m, color)
+    let sun_orbiters: [(&str, f64, f64, f64, u32); 8] = [
+        ("Mercury", 3.301e23, 2.4397e6, 5.791e10, 0xA0A0A0),
+        ("Venus", 4.867e24, 6.0518e6, 1.0821e11, 0xE0C080),
+        ("Earth", 5.972e24, 6.371e6, 1.496e11, 0x4040FF),
+        ("Mars", 6.417e23, 3.3895e6, 2.2794e11, 0xD05030),
+        ("Jupiter", 1.898e27, 6.9911e7, 7.7857e11, 0xD0A070),
+        ("Saturn", 5.683e26, 5.8232e7, 1.43353e12, 0xE0D090),
+        ("Uranus", 8.681e25, 2.5362e7, 2.87246e12, 0x80E0E0),
+        ("Neptune", 1.024e26, 2.4622e7, 4.49506e12, 0x4060FF),
+    ];
+
+    for (name, mass_kg, radius_m, orbit_m, color) in sun_orbiters {
+        let mass = units.mass_from_si(mass_kg);
+        let (position, velocity) = calculate_stable_orbit(
+            sun_position,
+            sun_velocity,
+            sun_mass,
+            units.length_from_si(orbit_m),
+            0.0, // circular orbit
+            big_gravity,
+        );
+
+        let mut planet = Planet::new(name.to_string(), units.length_from_si(radius_m), mass, position, velocity, color)
+            .with_description(format!("{} at its real size, mass and mean distance from the Sun.", name));
+        if name == "Earth" {
+            // By source as well, so saves and replays can restore it
+            planet = planet.with_texture_source("../resources/earth.png".to_string());
+            if let Ok(texture) = Texture::load_from_bytes(EARTH_TEXTURE_BYTES) {
+                planet = planet.with_texture(texture);
+            }
+        }
+        planets.push(planet);
+    }
+
+    // Moon orbiting Earth
+    let earth = planets.iter().find(|planet| planet.name == "Earth").unwrap().clone();
+    let (moon_position, moon_velocity) = calculate_stable_orbit(
+        earth.position,
+        earth.velocity,
+        earth.mass,
+        units.length_from_si(3.844e8),
         0.0, // circular orbit
         big_gravity,
     );
+    planets.push(
+        Planet::new(
+            "Moon".to_string(),
+            units.length_from_si(1.7374e6),
+            units.mass_from_si(7.342e22),
+            moon_position,
+            moon_velocity,
+            0xAAAAAA // gray
+        )
+        .with_description("Earth's Moon at its real size and distance.".to_string())
+        .with_role(BodyRole::Moon),
+    );
 
-    // Ben planet orbiting Sun (further out and larger)
-    let ben_orbit_radius = 25000.0;
-    let ben_mass = 8e12;
-    let (ben_position, ben_velocity) = calculate_stable_orbit(
-        sun_position,
-        sun_velocity,
-        sun_mass,
-        ben_orbit_radius,
-        0.1, // circular orbit
+    // Player in a 400 km low Earth orbit
+    let (player_position, player_velocity) = calculate_stable_orbit(
+        earth.position,
+        earth.velocity,
+        earth.mass,
+        units.length_from_si(6.771e6),
+        0.0, // circular orbit
         big_gravity,
     );
 
-    // Marty planet orbiting Sun (even further out and larger)
-    let marty_orbit_radius = 38000.0;
-    let marty_mass = 1e13;
-    let (marty_position, marty_velocity) = calculate_stable_orbit(
-        sun_position,
-        sun_velocity,
-        sun_mass,
-        marty_orbit_radius,
+    // A one tonne probe with 25 kN of thrust
+    let probe = Player::new(player_position, player_velocity, units.mass_from_si(1000.0), 0.0)
+        .with_engine_thrust(units.force_from_si(25.0e3));
+    let vessels = vec![probe];
+
+    Game::with_units(planets, vessels, units)
+}
+
+/// Two equal stars circling a shared barycenter, with a circumbinary planet
+pub fn create_binary_star() -> Game {
+    let big_gravity = DEFAULT_BIG_GRAVITY;
+
+    let barycenter_position = Vector2 { x: 0.0, y: 0.0 };
+    let barycenter_velocity = Vector2 { x: 0.0, y: 0.0 };
+
+    // Each star orbits the barycenter under the pull of the other: v = sqrt(G * M / (4 * a))
+    let star_mass = 5e14;
+    let star_orbit_radius = 4000.0;
+    let star_speed = (big_gravity * star_mass / (4.0 * star_orbit_radius)).sqrt();
+
+    // A circumbinary planet feels roughly the combined mass at the barycenter
+    let (planet_position, planet_velocity) = calculate_stable_orbit(
+        barycenter_position,
+        barycenter_velocity,
+        2.0 * star_mass,
+        30000.0,
         0.0, // circular orbit
         big_gravity,
     );
+    let planet_mass = 6e12;
 
-    // Moon orbiting Earth
-    let moon_orbit_radius = 1000.0;
-    let (moon_position, moon_velocity) = calculate_stable_orbit(
-        earth_position,
-        earth_velocity,
-        earth_mass,
-        moon_orbit_radius,
+    let (player_position, player_velocity) = calculate_stable_orbit(
+        planet_position,
+        planet_velocity,
+        planet_mass,
+        300.0,
         0.0, // circular orbit
         big_gravity,
     );
 
-    // Shirley orbiting Marty (small and light moon)
-    let shirley_orbit_radius = 1000.0;
-    let shirley_mass = 5e9; // Much lighter (1/10th of before)
-    let (shirley_position, shirley_velocity) = calculate_stable_orbit(
-        marty_position,
-        marty_velocity,
-        marty_mass,
-        shirley_orbit_radius,
+    let planets = vec![
+        Planet::new("Barycenter".to_string(), 0.0, 0.0, barycenter_position, barycenter_velocity, 0x808080)
+            .with_description("The shared center of mass that both stars orbit.".to_string())
+            .with_role(BodyRole::Barycenter),
+        Planet::new(
+            "Alpha".to_string(),
+            250.0,
+            star_mass,
+            Vector2 { x: star_orbit_radius, y: 0.0 },
+            Vector2 { x: 0.0, y: star_speed },
+            0xFFE080 // pale yellow
+        )
+        .with_description("The first of two twin stars.".to_string())
+        .with_luminosity(5e6)
+        .with_role(BodyRole::Star),
+        Planet::new(
+            "Beta".to_string(),
+            250.0,
+            star_mass,
+            Vector2 { x: -star_orbit_radius, y: 0.0 },
+            Vector2 { x: 0.0, y: -star_speed },
+            0xFFA060 // orange
+        )
+        .with_description("The second of two twin stars.".to_string())
+        .with_luminosity(5e6)
+        .with_role(BodyRole::Star),
+        Planet::new("Tatooine".to_string(), 150.0, planet_mass, planet_position, planet_velocity, 0xD0B080)
+            .with_description("A desert planet with two suns in its sky.".to_string()),
+    ];
+
+    let vessels = vec![Player::new(player_position, player_velocity, 1.0, 0.0)];
+
+    Game::new(planets, vessels)
+}
+
+/// A star-less pair of rogue planets orbiting each other, one with a moon
+pub fn create_rogue_planets() -> Game {
+    let big_gravity = DEFAULT_BIG_GRAVITY;
+
+    let host_position = Vector2 { x: 0.0, y: 0.0 };
+    let host_velocity = Vector2 { x: 0.0, y: 0.0 };
+    let host_mass = 1e13;
+
+    let (companion_position, companion_velocity) = calculate_stable_orbit(
+        host_position,
+        host_velocity,
+        host_mass,
+        8000.0,
         0.0, // circular orbit
         big_gravity,
     );
 
-    // Puckman orbiting Marty (another moon, slightly larger and further out)
-    let puckman_orbit_radius = 2000.0; // Much further away
-    let puckman_mass = 7e9; // Much lighter
-    let (puckman_position, puckman_velocity) = calculate_stable_orbit(
-        marty_position,
-        marty_velocity,
-        marty_mass,
-        puckman_orbit_radius,
+    let (moon_position, moon_velocity) = calculate_stable_orbit(
+        host_position,
+        host_velocity,
+        host_mass,
+        1500.0,
         0.0, // circular orbit
         big_gravity,
     );
 
-    // Player orbiting Earth
-    let player_orbit_radius = 300.0;
     let (player_position, player_velocity) = calculate_stable_orbit(
-        earth_position,
-        earth_velocity,
-        earth_mass,
-        player_orbit_radius,
+        host_position,
+        host_velocity,
+        host_mass,
+        600.0,
         0.0, // circular orbit
         big_gravity,
     );
 
-    // Load Earth texture from embedded bytes
-    let mut earth = Planet::new(
-        "Earth".to_string(),
-        150.0,
-        earth_mass,
-        earth_position,
-        earth_velocity,
-        0x4040FF // blue fallback color
-    )
-    .with_description("A small blue planet with an atmosphere primarily composed of nitrogen and oxygen. It is the only known planet to support life.".to_string());
-
-    if let Ok(texture) = Texture::load_from_bytes(EARTH_TEXTURE_BYTES) {
-        earth = earth.with_texture(texture);
-    }
-
-    // Load Ben planet texture from embedded bytes
-    let mut ben_planet = Planet::new(
-        "Ben".to_string(),
-        250.0, // Larger radius than Earth (150)
-        ben_mass,
-        ben_position,
-        ben_velocity,
-        0xFF8040 // orange fallback color
-    )
-    .with_description("A slightly eccentric planet that was originally an exoplanet but was captured by the Sun's gravity.".to_string());
-
-    if let Ok(texture) = Texture::load_from_bytes(BEN_TEXTURE_BYTES) {
-        ben_planet = ben_planet.with_texture(texture);
-    }
-
-    // Load Marty planet texture from embedded bytes
-    let mut marty_planet = Planet::new(
-        "Marty".to_string(),
-        350.0, // Even larger radius than Ben (250)
-        marty_mass,
-        marty_position,
-        marty_velocity,
-        0xFF40FF // magenta fallback color
-    )
-    .with_description("The largest planet in the outer system with a moon. Marty was one of the first planets formed in the solar system.".to_string());
-
-    if let Ok(texture) = Texture::load_from_bytes(MARTY_TEXTURE_BYTES) {
-        marty_planet = marty_planet.with_texture(texture);
-    }
-
-    // Load Shirley moon texture from embedded bytes
-    let mut shirley_moon = Planet::new(
-        "Shirley".to_string(),
-        40.0, // Small radius (between Moon at 20 and player orbit)
-        shirley_mass,
-        shirley_position,
-        shirley_velocity,
-        0xFFFFAA // light yellow fallback color
-    )
-    .with_description("One of the two moons of Marty. Shirley was discovered before Marty and was the main focus of astronomy until Marty was discovered. Nowdays many people don't even know about Shirley.".to_string());
-
-    if let Ok(texture) = Texture::load_from_bytes(SHIRLEY_TEXTURE_BYTES) {
-        shirley_moon = shirley_moon.with_texture(texture);
-    }
-
-    // Load Puckman moon texture from embedded bytes
-    let mut puckman_moon = Planet::new(
-        "Puckman".to_string(),
-        55.0, // Slightly larger than Shirley
-        puckman_mass,
-        puckman_position,
-        puckman_velocity,
-        0xFFFF00 // yellow fallback color
-    )
-    .with_description("The second moon of Marty, larger and further out than Shirley. Puckman has a distinctive circular shape.".to_string());
-
-    if let Ok(texture) = Texture::load_from_bytes(PUCKMAN_TEXTURE_BYTES) {
-        puckman_moon = puckman_moon.with_texture(texture);
-    }
-
     let planets = vec![
-        Planet::new(
-            "Sun".to_string(),
-            300.0,
-            sun_mass,
-            sun_position,
-            sun_velocity,
-            0xFFFF00 // yellow
-        )
-        .with_description("The star at the center of our solar system. The Sun is the primary source of energy for the solar system and is responsible for the planets' orbits.".to_string()),
-        earth,
-        ben_planet,
-        marty_planet,
-        shirley_moon,
-        puckman_moon,
-        Planet::new(
-            "Moon".to_string(),
-            20.0,
-            1e11,
-            moon_position,
-            moon_velocity,
-            0xAAAAAA // gray
-        )
-        .with_description("Earths only natural satellite, but not for long. The earth-moon system is very unstable.".to_string()),
+        Planet::new("Nomad".to_string(), 300.0, host_mass, host_position, host_velocity, 0x6060A0)
+            .with_description("A cold planet drifting between the stars.".to_string()),
+        Planet::new("Wanderer".to_string(), 120.0, 1e12, companion_position, companion_velocity, 0x80A080)
+            .with_description("Nomad's smaller companion, bound to it since both were ejected from their system.".to_string()),
+        Planet::new("Lantern".to_string(), 30.0, 2e10, moon_position, moon_velocity, 0xC0C0C0)
+            .with_description("A small moon of Nomad.".to_string())
+            .with_role(BodyRole::Moon),
     ];
 
-    let player = Player::new(
-        player_position,
-        player_velocity,
-        1.0,
-        0.0
-    );
+    let vessels = vec![Player::new(player_position, player_velocity, 1.0, 0.0)];
 
-    Game::new(planets, player)
+    Game::new(planets, vessels)
 }
-
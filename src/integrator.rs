@@ -0,0 +1,131 @@
+//! Alternative integrators for headless runs; the game itself always uses
+//! Game::update's semi-implicit Euler
+
+use crate::game::Game;
+use crate::planet::BodyRole;
+use crate::vector2::Vector2;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Integrator {
+    SemiImplicitEuler, // Game::update, first order and symplectic
+    Leapfrog,          // Kick-drift-kick, second order and symplectic
+    RungeKutta4,       // Classic fourth order, not symplectic
+}
+
+impl Integrator {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "euler" => Ok(Integrator::SemiImplicitEuler),
+            "leapfrog" => Ok(Integrator::Leapfrog),
+            "rk4" => Ok(Integrator::RungeKutta4),
+            _ => Err(format!("Unknown integrator '{}' (expected euler, leapfrog or rk4)", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::SemiImplicitEuler => "euler",
+            Integrator::Leapfrog => "leapfrog",
+            Integrator::RungeKutta4 => "rk4",
+        }
+    }
+
+    /// Advance the game by `dt`. Barycenters coast and landed vessels stay put, as in Game::update.
+    pub fn advance(&self, game: &mut Game, dt: f64) {
+        match self {
+            Integrator::SemiImplicitEuler => game.update(dt),
+            Integrator::Leapfrog => {
+                let (planet_accelerations, vessel_accelerations) = game.accelerations();
+                kick(game, &planet_accelerations, &vessel_accelerations, dt / 2.0);
+                drift(game, dt);
+                let (planet_accelerations, vessel_accelerations) = game.accelerations();
+                kick(game, &planet_accelerations, &vessel_accelerations, dt / 2.0);
+                game.resolve_surface_contacts();
+            }
+            Integrator::RungeKutta4 => {
+                runge_kutta_4(game, dt);
+                game.resolve_surface_contacts();
+            }
+        }
+    }
+}
+
+fn kick(game: &mut Game, planet_accelerations: &[Vector2], vessel_accelerations: &[Vector2], dt: f64) {
+    for (planet, acceleration) in game.planets.iter_mut().zip(planet_accelerations) {
+        if planet.role != BodyRole::Barycenter {
+            planet.velocity = planet.velocity.add(&acceleration.scale(dt));
+        }
+    }
+    for (vessel, acceleration) in game.vessels.iter_mut().zip(vessel_accelerations) {
+        if vessel.landed.is_none() {
+            vessel.velocity = vessel.velocity.add(&acceleration.scale(dt));
+        }
+    }
+}
+
+fn drift(game: &mut Game, dt: f64) {
+    for planet in &mut game.planets {
+        planet.position = planet.position.add(&planet.velocity.scale(dt));
+    }
+    for vessel in game.vessels.iter_mut().filter(|vessel| vessel.landed.is_none()) {
+        vessel.position = vessel.position.add(&vessel.velocity.scale(dt));
+    }
+}
+
+/// Position and velocity of every planet, then every vessel
+fn states(game: &Game) -> Vec<(Vector2, Vector2)> {
+    let planets = game.planets.iter().map(|planet| (planet.position, planet.velocity));
+    planets.chain(game.vessels.iter().map(|vessel| (vessel.position, vessel.velocity))).collect()
+}
+
+fn set_states(game: &mut Game, states: &[(Vector2, Vector2)]) {
+    let (planet_states, vessel_states) = states.split_at(game.planets.len());
+    for (planet, &(position, velocity)) in game.planets.iter_mut().zip(planet_states) {
+        planet.position = position;
+        planet.velocity = velocity;
+    }
+    for (vessel, &(position, velocity)) in game.vessels.iter_mut().zip(vessel_states) {
+        vessel.position = position;
+        vessel.velocity = velocity;
+    }
+}
+
+/// Rate of change of every state: its velocity and acceleration, zero for whatever is held still
+fn rates(game: &Game) -> Vec<(Vector2, Vector2)> {
+    let zero = Vector2 { x: 0.0, y: 0.0 };
+    let (planet_accelerations, vessel_accelerations) = game.accelerations();
+    let planets = game.planets.iter().zip(planet_accelerations).map(|(planet, acceleration)| {
+        (planet.velocity, if planet.role == BodyRole::Barycenter { zero } else { acceleration })
+    });
+    let vessels = game.vessels.iter().zip(vessel_accelerations).map(|(vessel, acceleration)| {
+        if vessel.landed.is_none() { (vessel.velocity, acceleration) } else { (zero, zero) }
+    });
+    planets.chain(vessels).collect()
+}
+
+/// States moved along `rates` for `dt`
+fn offset(states: &[(Vector2, Vector2)], rates: &[(Vector2, Vector2)], dt: f64) -> Vec<(Vector2, Vector2)> {
+    states
+        .iter()
+        .zip(rates)
+        .map(|((position, velocity), (dx, dv))| (position.add(&dx.scale(dt)), velocity.add(&dv.scale(dt))))
+        .collect()
+}
+
+fn runge_kutta_4(game: &mut Game, dt: f64) {
+    let start = states(game);
+    let k1 = rates(game);
+    set_states(game, &offset(&start, &k1, dt / 2.0));
+    let k2 = rates(game);
+    set_states(game, &offset(&start, &k2, dt / 2.0));
+    let k3 = rates(game);
+    set_states(game, &offset(&start, &k3, dt));
+    let k4 = rates(game);
+
+    // Weighted average of the four slopes: (k1 + 2 k2 + 2 k3 + k4) / 6
+    let mut state = start;
+    for (k, weight) in [(&k1, 1.0), (&k2, 2.0), (&k3, 2.0), (&k4, 1.0)] {
+        state = offset(&state, k, dt * weight / 6.0);
+    }
+    set_states(game, &state);
+}
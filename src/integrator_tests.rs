@@ -0,0 +1,73 @@
+//! The headless integrators converge at their stated order and hold still what Game::update does.
+
+use crate::game::Game;
+use crate::initial_universe::calculate_stable_orbit;
+use crate::integrator::Integrator;
+use crate::planet::{BodyRole, Planet};
+use crate::player::Player;
+use crate::vector2::Vector2;
+
+const MASS: f64 = 1.0e12;
+const ORBIT_RADIUS: f64 = 1000.0;
+
+/// A vessel on a circular orbit around a planet held at the origin, vessels
+/// having no pull of their own
+fn orbit() -> Game {
+    let origin = Vector2 { x: 0.0, y: 0.0 };
+    let planet = Planet::new("Planet".to_string(), 10.0, MASS, origin, origin, 0x4040FF);
+    let mut game = Game::new(vec![planet], Vec::new());
+    let (position, velocity) = calculate_stable_orbit(origin, origin, MASS, ORBIT_RADIUS, 0.0, game.big_gravity);
+    game.vessels.push(Player::new(position, velocity, 1.0, 0.0));
+    game
+}
+
+/// How far the vessel is from where it should be after a quarter period in `steps` steps
+fn quarter_orbit_error(integrator: Integrator, steps: usize) -> f64 {
+    let mut game = orbit();
+    let speed = game.vessels[0].velocity.magnitude();
+    let dt = std::f64::consts::FRAC_PI_2 * ORBIT_RADIUS / speed / steps as f64;
+    for _ in 0..steps {
+        integrator.advance(&mut game, dt);
+    }
+    // A quarter turn on, the vessel is where its starting velocity pointed
+    let expected = orbit().vessels[0].velocity.normalize().scale(ORBIT_RADIUS);
+    game.vessels[0].position.distance(&expected)
+}
+
+#[test]
+fn integrators_converge_at_their_order() {
+    for (name, order) in [("euler", 1), ("leapfrog", 2), ("rk4", 4)] {
+        let integrator = Integrator::parse(name).expect("integrator exists");
+        assert_eq!(integrator.name(), name);
+        // Halving the step divides the error by about 2^order
+        let ratio = quarter_orbit_error(integrator, 100) / quarter_orbit_error(integrator, 200);
+        let expected = 2f64.powi(order);
+        assert!((ratio / expected - 1.0).abs() < 0.25, "{} error fell {} times with half the step, not {}", name, ratio, expected);
+    }
+    assert!(Integrator::parse("verlet").is_err());
+}
+
+#[test]
+fn barycenters_coast_and_landed_vessels_stay_put() {
+    for integrator in [Integrator::SemiImplicitEuler, Integrator::Leapfrog, Integrator::RungeKutta4] {
+        let mut game = orbit();
+        let velocity = Vector2 { x: 3.0, y: -4.0 };
+        let barycenter = Planet::new("Barycenter".to_string(), 0.0, 0.0, Vector2 { x: 0.0, y: 500.0 }, velocity, 0)
+            .with_role(BodyRole::Barycenter);
+        game.planets.push(barycenter);
+        // Touching down settles the vessel on the surface
+        game.vessels[0].position = Vector2 { x: 0.0, y: 10.5 };
+        game.vessels[0].velocity = Vector2 { x: 0.0, y: 0.0 };
+        game.resolve_surface_contacts();
+        assert!(game.vessels[0].landed.is_some());
+
+        let landed_at = game.vessels[0].position;
+        for _ in 0..10 {
+            integrator.advance(&mut game, 0.1);
+        }
+        let barycenter = &game.planets[1];
+        assert_eq!((barycenter.velocity.x, barycenter.velocity.y), (velocity.x, velocity.y), "{}", integrator.name());
+        assert!(barycenter.position.distance(&Vector2 { x: 3.0, y: 496.0 }) < 1e-9, "{}", integrator.name());
+        assert!(game.vessels[0].position.distance(&landed_at) < 1e-6, "{}", integrator.name());
+    }
+}
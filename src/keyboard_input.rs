@@ -1,26 +1,31 @@
-use crate::game::Game;
+use crate::det_math;
+use crate::game::{Game, TRAJECTORY_DT};
 
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "native", not(target_arch = "wasm32")))]
 use winit::event::{KeyEvent, ElementState};
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "native", not(target_arch = "wasm32")))]
 use winit::keyboard::{KeyCode, PhysicalKey};
 
+#[allow(dead_code)]
 pub struct InputState {
     pub rotate_left: bool,
     pub rotate_right: bool,
     pub thrust: bool,
+    pub rewind: bool,
 }
 
+#[allow(dead_code)]
 impl InputState {
     pub fn new() -> Self {
         Self {
             rotate_left: false,
             rotate_right: false,
             thrust: false,
+            rewind: false,
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(all(feature = "native", not(target_arch = "wasm32")))]
     pub fn handle_key_event(&mut self, event: &KeyEvent) {
         let pressed = event.state == ElementState::Pressed;
 
@@ -36,32 +41,135 @@ impl InputState {
             PhysicalKey::Code(KeyCode::ArrowUp) => {
                 self.thrust = pressed;
             }
+            PhysicalKey::Code(KeyCode::KeyR) => {
+                self.rewind = pressed;
+            }
             _ => {}
         }
     }
 
     pub fn apply_to_game(&self, game: &mut Game, dt: f64) {
-        // Update player rotation (doesn't change trajectory)
-        let rotation_speed = 3.0; // radians per second
-        if self.rotate_left {
-            game.player.rotation -= rotation_speed * dt;
+        // Only recalculate trajectory when thrust changes velocity
+        if self.apply_controls(game, dt) {
+            game.recalculate_trajectories();
         }
-        if self.rotate_right {
-            game.player.rotation += rotation_speed * dt;
+    }
+
+    /// Run one frame of the main loop: `dt` real seconds at `time_warp`, with
+    /// leftover time carried in `time_accumulator`. Live play and replays both
+    /// go through here so a recorded frame plays back exactly as it ran.
+    pub fn advance_frame(&self, game: &mut Game, time_accumulator: &mut f64, dt: f64, time_warp: f64, deterministic: bool) {
+        // Accumulate time with time warp multiplier
+        *time_accumulator += dt * time_warp;
+        let steps_to_advance = (*time_accumulator / TRAJECTORY_DT) as usize;
+
+        if self.rewind {
+            // Play recorded history backward
+            for _ in 0..steps_to_advance {
+                if !game.rewind_step() {
+                    break;
+                }
+            }
+            *time_accumulator -= steps_to_advance as f64 * TRAJECTORY_DT;
+            return;
         }
 
-        // Apply thrust force if thrusting (changes trajectory)
-        if self.thrust {
-            let thrust_force = 25.0; // thrust force magnitude
-            let thrust_x = game.player.rotation.sin() * thrust_force;
-            let thrust_y = -game.player.rotation.cos() * thrust_force;
+        // Branch a new timeline from wherever rewinding stopped
+        if !game.cached_trajectories.is_valid {
+            game.recalculate_trajectories();
+        }
 
-            game.player.velocity.x += thrust_x / game.player.mass * dt;
-            game.player.velocity.y += thrust_y / game.player.mass * dt;
+        if deterministic {
+            // Apply input once per fixed tick instead of once per frame
+            self.run_fixed_ticks(game, steps_to_advance);
+            *time_accumulator -= steps_to_advance as f64 * TRAJECTORY_DT;
+            return;
+        }
 
-            // Only recalculate trajectory when thrust changes velocity
+        // Apply input to game (this will recalculate trajectory if input changed)
+        self.apply_to_game(game, dt);
+
+        if steps_to_advance > 0 {
+            // Advance multiple steps at once
+            for _ in 0..steps_to_advance {
+                game.advance_trajectory();
+            }
+
+            // Batch extend trajectories to maintain look-ahead
+            game.extend_trajectories(steps_to_advance);
+
+            *time_accumulator -= steps_to_advance as f64 * TRAJECTORY_DT;
+        }
+
+        // Capture a docking target once within tolerances
+        game.try_dock();
+
+        // Break up bodies that stray inside a Roche limit
+        game.check_tidal_disruption();
+    }
+
+    /// Advance whole fixed input ticks: controls are applied with the same dt
+    /// every tick, so a run depends only on the inputs and not on frame timing
+    pub fn run_fixed_ticks(&self, game: &mut Game, ticks: usize) {
+        if ticks == 0 {
+            return;
+        }
+
+        for _ in 0..ticks {
+            // A burn invalidates the cache; the step is then simulated directly
+            if self.apply_controls(game, TRAJECTORY_DT) {
+                game.cached_trajectories.is_valid = false;
+            }
+            game.advance_trajectory();
+            game.try_dock();
+            game.check_tidal_disruption();
+        }
+
+        if game.cached_trajectories.is_valid {
+            game.extend_trajectories(ticks);
+        } else {
             game.recalculate_trajectories();
         }
     }
-}
 
+    /// Rotate and thrust the active vessel, returning whether its velocity changed
+    fn apply_controls(&self, game: &mut Game, dt: f64) -> bool {
+        // Update player rotation (only changes the trajectory of sail vessels)
+        // A landed ship is held aligned with the surface normal instead
+        let rotation_speed = 3.0; // radians per second
+        let mut sail_turned = false;
+        if game.player().landed.is_none() {
+            let player = game.player_mut();
+            if self.rotate_left {
+                player.rotation -= rotation_speed * dt;
+            }
+            if self.rotate_right {
+                player.rotation += rotation_speed * dt;
+            }
+            sail_turned = (self.rotate_left || self.rotate_right) && player.sail_area_to_mass > 0.0;
+        }
+
+        // Apply thrust force if thrusting (changes trajectory)
+        let thrust_force = game.player().engine_thrust;
+        if !self.thrust || thrust_force == 0.0 {
+            return sail_turned;
+        }
+
+        // Stay on the surface until thrust overcomes local gravity; a wreck stays for good
+        if let Some(contact) = game.player().landed {
+            if contact.crashed || thrust_force / game.player().mass <= game.surface_gravity(contact.planet_index) {
+                return sail_turned;
+            }
+            game.player_mut().landed = None;
+        }
+
+        let player = game.player_mut();
+        let thrust_x = det_math::sin(player.rotation) * thrust_force;
+        let thrust_y = -det_math::cos(player.rotation) * thrust_force;
+
+        player.velocity.x += thrust_x / player.mass * dt;
+        player.velocity.y += thrust_y / player.mass * dt;
+
+        true
+    }
+}
@@ -0,0 +1,100 @@
+//! Lagrange point computation for a primary/secondary pair
+
+use crate::det_math;
+use crate::game::Game;
+use crate::planet::BodyRole;
+use crate::player::Player;
+use crate::vector2::Vector2;
+
+// Newton iterations used to solve for the collinear points
+const COLLINEAR_ITERATIONS: usize = 50;
+
+/// Position and co-rotating velocity of a Lagrange point
+#[derive(Clone, Copy)]
+pub struct LagrangePoint {
+    pub position: Vector2,
+    #[allow(dead_code)]
+    pub velocity: Vector2,
+}
+
+/// Find the massive body a planet orbits; barycenters carry no mass to form a pair with
+pub fn primary_of(game: &Game, secondary: usize) -> Option<usize> {
+    game.reference_body(secondary)
+        .filter(|&primary| game.planets[primary].role != BodyRole::Barycenter)
+}
+
+/// Compute L1-L5 for a pair from their current positions and masses
+pub fn lagrange_points(game: &Game, primary: usize, secondary: usize) -> [LagrangePoint; 5] {
+    let p = &game.planets[primary];
+    let s = &game.planets[secondary];
+
+    let total_mass = p.mass + s.mass;
+    let mu = s.mass / total_mass;
+
+    let offset = s.position.subtract(&p.position);
+    let r = offset.magnitude();
+    let axis = offset.scale(1.0 / r);
+
+    let barycenter = p.position.scale(p.mass).add(&s.position.scale(s.mass)).scale(1.0 / total_mass);
+    let barycenter_velocity = p.velocity.scale(p.mass).add(&s.velocity.scale(s.mass)).scale(1.0 / total_mass);
+
+    // Circular-orbit angular velocity, signed by the pair's sense of rotation
+    let relative_velocity = s.velocity.subtract(&p.velocity);
+    let sense = if offset.cross(&relative_velocity) >= 0.0 { 1.0 } else { -1.0 };
+    let omega = sense * (game.big_gravity * total_mass / (r * r * r)).sqrt();
+
+    // Collinear points, in units of r along the axis measured from the barycenter
+    let hill = det_math::cbrt(mu / 3.0);
+    let l1 = solve_collinear(mu, 1.0 - mu - hill);
+    let l2 = solve_collinear(mu, 1.0 - mu + hill);
+    let l3 = solve_collinear(mu, -1.0 - 5.0 * mu / 12.0);
+
+    // Triangular points form equilateral triangles with the pair; L4 leads
+    let leading = axis.rotate(sense * std::f64::consts::FRAC_PI_3);
+    let trailing = axis.rotate(-sense * std::f64::consts::FRAC_PI_3);
+
+    let co_rotating = |position: Vector2| {
+        let arm = position.subtract(&barycenter);
+        LagrangePoint {
+            position,
+            velocity: barycenter_velocity.add(&Vector2 { x: -omega * arm.y, y: omega * arm.x }),
+        }
+    };
+
+    [
+        co_rotating(barycenter.add(&axis.scale(l1 * r))),
+        co_rotating(barycenter.add(&axis.scale(l2 * r))),
+        co_rotating(barycenter.add(&axis.scale(l3 * r))),
+        co_rotating(p.position.add(&leading.scale(r))),
+        co_rotating(p.position.add(&trailing.scale(r))),
+    ]
+}
+
+/// Place a new test vessel at a Lagrange point, moving with the pair
+#[allow(dead_code)]
+pub fn place_vessel_at(game: &mut Game, primary: usize, secondary: usize, point: usize) {
+    let target = lagrange_points(game, primary, secondary)[point];
+    game.spawn_vessel(Player::new(target.position, target.velocity, 1.0, 0.0));
+}
+
+/// Solve the collinear equilibrium condition with Newton's method, normalized so G*M = r = omega = 1
+fn solve_collinear(mu: f64, guess: f64) -> f64 {
+    let mut x = guess;
+    for _ in 0..COLLINEAR_ITERATIONS {
+        let d1 = x + mu;
+        let d2 = x - 1.0 + mu;
+        let r1_cubed = d1.abs().powi(3);
+        let r2_cubed = d2.abs().powi(3);
+
+        let net_force = x - (1.0 - mu) * d1 / r1_cubed - mu * d2 / r2_cubed;
+        let slope = 1.0 + 2.0 * (1.0 - mu) / r1_cubed + 2.0 * mu / r2_cubed;
+
+        let step = net_force / slope;
+        x -= step;
+        if step.abs() < 1e-14 {
+            break;
+        }
+    }
+
+    x
+}
@@ -1,4 +1,5 @@
-// WASM entry point
+//! WASM entry point
+
 #![cfg(target_arch = "wasm32")]
 
 mod planet;
@@ -11,14 +12,35 @@ mod keyboard_input;
 mod texture;
 mod sprite_renderer;
 mod font;
+mod docking;
+mod lagrange;
+mod det_math;
+mod units;
+mod history;
+mod tidal;
+mod flyby;
+mod scenario;
+mod generator;
+mod save;
+mod quicksave;
+mod replay;
+mod ghost;
+mod ephemeris;
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlCanvasElement, CanvasRenderingContext2d, ImageData};
 use std::cell::RefCell;
-use crate::game::{Game, TRAJECTORY_DT};
-use crate::initial_universe::create_universe;
+use crate::ephemeris::{Ephemeris, EphemerisFormat, EphemerisOptions};
+use crate::game::Game;
+use crate::ghost::Ghost;
+use crate::initial_universe::{create_universe, scenario_by_name};
 use crate::keyboard_input::InputState;
+use crate::lagrange::primary_of;
+use crate::quicksave::Quicksaves;
+use crate::replay::{FrameInput, Playback, Recording, ReplayAction, SEEK_SECONDS};
+use crate::save::{load_game, save_game, ViewState};
+use crate::render::{info_panel_button_at, planet_at_screen, render_game, Camera, InfoPanelButton, Overlay, TrajectoryFrame};
 
 thread_local! {
     static APP_STATE: RefCell<Option<AppState>> = RefCell::new(None);
@@ -31,9 +53,109 @@ struct AppState {
     last_time: f64,
     zoom_level: f64,
     time_warp: f64,
-    show_absolute_trajectories: bool,
+    trajectory_frame: TrajectoryFrame,
     selected_planet: Option<usize>,
+    lagrange_pair: Option<(usize, usize)>,
+    deterministic: bool,
     mouse_pos: (f64, f64),
+    quicksaves: Quicksaves,
+    recording: Option<Recording>,
+    last_recording: Option<Recording>,
+    playback: Option<Playback>,
+    ghost: Option<Ghost>,
+}
+
+impl AppState {
+    fn view_state(&self) -> ViewState {
+        ViewState {
+            zoom_level: self.zoom_level,
+            time_warp: self.time_warp,
+            selected_planet: self.selected_planet,
+        }
+    }
+
+    /// Switch to a restored game and view, ending any recording or replay of the old one
+    fn restore(&mut self, game: Game, view: ViewState) {
+        self.stop_recording();
+        if let Some(playback) = self.playback.take() {
+            self.last_recording = Some(playback.into_recording());
+        }
+        self.ghost = None;
+        self.game = game;
+        self.zoom_level = view.zoom_level;
+        self.time_warp = view.time_warp;
+        self.selected_planet = view.selected_planet;
+        self.time_accumulator = 0.0;
+        // Frames and Lagrange pairs refer to body indices that may have changed
+        self.trajectory_frame = TrajectoryFrame::Relative;
+        self.lagrange_pair = None;
+    }
+
+    /// Apply a command to the game, recording it if a recording is running
+    fn perform(&mut self, action: ReplayAction) {
+        if let Some(recording) = &mut self.recording {
+            recording.push_action(action);
+        }
+        action.apply(&mut self.game);
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            self.last_recording = Some(recording);
+        }
+    }
+
+    fn toggle_recording(&mut self) {
+        if self.recording.is_some() {
+            self.stop_recording();
+        } else {
+            let view = self.view_state();
+            self.recording = Some(Recording::start(&mut self.game, view, self.time_accumulator));
+        }
+    }
+
+    /// Restart from a recording's first frame and play it back
+    fn start_playback(&mut self, recording: Recording) -> Result<(), String> {
+        let (playback, game, view) = Playback::start(recording)?;
+        self.restore(game, view);
+        self.playback = Some(playback);
+        Ok(())
+    }
+
+    /// Play the last recording; stopping hands control back mid-flight
+    fn toggle_playback(&mut self) {
+        if let Some(playback) = self.playback.take() {
+            self.last_recording = Some(playback.into_recording());
+            return;
+        }
+        self.stop_recording();
+        if let Some(recording) = self.last_recording.take()
+            && let Err(e) = self.start_playback(recording)
+        {
+            web_sys::console::log_1(&e.into());
+        }
+    }
+
+    /// Restart from a recording's first frame with its ghost flying alongside
+    fn start_race(&mut self, recording: Recording) -> Result<(), String> {
+        let (ghost, game, view) = Ghost::race(recording, self.selected_planet)?;
+        self.restore(game, view);
+        self.ghost = Some(ghost);
+        Ok(())
+    }
+
+    /// Race a ghost of the last recording, or call the race off
+    fn toggle_ghost(&mut self) {
+        if self.ghost.take().is_some() {
+            return;
+        }
+        self.stop_recording();
+        if let Some(recording) = self.last_recording.clone()
+            && let Err(e) = self.start_race(recording)
+        {
+            web_sys::console::log_1(&e.into());
+        }
+    }
 }
 
 #[wasm_bindgen(start)]
@@ -44,7 +166,16 @@ pub fn main() {
 
 #[wasm_bindgen]
 pub fn init_game() {
-    let game = create_universe();
+    init_game_with_scenario("default");
+}
+
+/// Start a named scenario, falling back to the default universe
+#[wasm_bindgen]
+pub fn init_game_with_scenario(name: &str) {
+    let game = scenario_by_name(name).unwrap_or_else(|| {
+        web_sys::console::log_1(&format!("Unknown scenario '{}', using default", name).into());
+        create_universe()
+    });
 
     let state = AppState {
         game,
@@ -53,9 +184,16 @@ pub fn init_game() {
         last_time: js_sys::Date::now(),
         zoom_level: 1.0,
         time_warp: 1.0,
-        show_absolute_trajectories: false,
+        trajectory_frame: TrajectoryFrame::Relative,
         selected_planet: None,
+        lagrange_pair: None,
+        deterministic: false,
         mouse_pos: (0.0, 0.0),
+        quicksaves: Quicksaves::new(),
+        recording: None,
+        last_recording: None,
+        playback: None,
+        ghost: None,
     };
 
     APP_STATE.with(|app| {
@@ -84,34 +222,45 @@ pub fn update_and_render(canvas_id: &str) -> Result<(), JsValue> {
             let dt = (now - state.last_time) / 1000.0; // Convert to seconds
             state.last_time = now;
 
-            // Apply input
-            state.input_state.apply_to_game(&mut state.game, dt);
-
-            // Accumulate time with time warp multiplier
-            state.time_accumulator += dt * state.time_warp;
-
-            let steps_to_advance = (state.time_accumulator / TRAJECTORY_DT) as usize;
-            if steps_to_advance > 0 {
-                for _ in 0..steps_to_advance {
-                    state.game.advance_trajectory();
+            if let Some(playback) = &mut state.playback {
+                // Replays run on their own recorded clock and controls
+                playback.update(&mut state.game);
+            } else {
+                if let Some(recording) = &mut state.recording {
+                    recording.push_frame(FrameInput::capture(&state.input_state, dt, state.time_warp, state.deterministic));
+                }
+                state.input_state.advance_frame(&mut state.game, &mut state.time_accumulator, dt, state.time_warp, state.deterministic);
+                if let Some(ghost) = &mut state.ghost {
+                    ghost.update(&state.game);
                 }
-                state.game.extend_trajectories(steps_to_advance);
-                state.time_accumulator -= steps_to_advance as f64 * TRAJECTORY_DT;
             }
 
             // Render to buffer
             let mut buffer = vec![0u32; width * height];
-            crate::render::render_game(
-                &mut buffer,
+            // Show the replay's controls and warp while one is playing
+            let (input_state, time_warp, replay_status) = match (&state.playback, &state.recording) {
+                (Some(playback), _) => (playback.input_state(), playback.time_warp(), Some(playback.status())),
+                (None, Some(recording)) => (&state.input_state, state.time_warp, Some(format!("REC {:.1} s", recording.duration()))),
+                (None, None) => (&state.input_state, state.time_warp, None),
+            };
+            let camera = Camera {
                 width,
                 height,
-                &state.game,
-                state.input_state.thrust,
-                state.zoom_level,
-                state.time_warp,
-                state.show_absolute_trajectories,
-                state.selected_planet,
-            );
+                zoom_level: state.zoom_level,
+                frame: state.trajectory_frame,
+                selected_planet: state.selected_planet,
+                lagrange_pair: state.lagrange_pair,
+                time_warp,
+                thrusting: input_state.thrust,
+                deterministic: state.deterministic,
+            };
+            let overlay = Overlay {
+                rewinding: input_state.rewind,
+                quicksaves: Some(&state.quicksaves),
+                replay_status: replay_status.as_deref(),
+                ghost: state.ghost.as_ref(),
+            };
+            render_game(&mut buffer, &state.game, &camera, &overlay);
 
             // Convert buffer to ImageData and draw to canvas
             let context = canvas
@@ -147,10 +296,42 @@ pub fn update_and_render(canvas_id: &str) -> Result<(), JsValue> {
 pub fn handle_key_down(key_code: &str) {
     APP_STATE.with(|app| {
         if let Some(state) = app.borrow_mut().as_mut() {
+            // Replay controls take over keys, and anything that would change the replayed game is ignored
+            if let Some(playback) = &mut state.playback {
+                let handled = match key_code {
+                    "KeyP" => {
+                        playback.paused = !playback.paused;
+                        true
+                    }
+                    "Period" => {
+                        playback.faster();
+                        true
+                    }
+                    "Comma" => {
+                        playback.slower();
+                        true
+                    }
+                    "ArrowRight" => {
+                        playback.seek(&mut state.game, SEEK_SECONDS);
+                        true
+                    }
+                    "ArrowLeft" => {
+                        playback.seek(&mut state.game, -SEEK_SECONDS);
+                        true
+                    }
+                    "KeyV" | "KeyU" | "KeyK" | "F5" | "F9" | "F10" | "KeyG" | "Digit1" | "Digit2" | "Digit3" | "Digit4" | "Digit5" => true,
+                    _ => false,
+                };
+                if handled {
+                    return;
+                }
+            }
+
             match key_code {
                 "KeyA" | "ArrowLeft" => state.input_state.rotate_left = true,
                 "KeyD" | "ArrowRight" => state.input_state.rotate_right = true,
                 "Space" | "KeyW" | "ArrowUp" => state.input_state.thrust = true,
+                "KeyR" => state.input_state.rewind = true,
                 "Equal" | "NumpadAdd" => state.zoom_level *= 1.2,
                 "Minus" | "NumpadSubtract" => state.zoom_level /= 1.2,
                 "Period" => {
@@ -161,13 +342,148 @@ pub fn handle_key_down(key_code: &str) {
                     state.time_warp /= 2.0;
                     state.time_warp = state.time_warp.max(1.0);
                 }
-                "Tab" => state.show_absolute_trajectories = !state.show_absolute_trajectories,
+                "Tab" => {
+                    state.trajectory_frame = if state.trajectory_frame == TrajectoryFrame::Absolute {
+                        TrajectoryFrame::Relative
+                    } else {
+                        TrajectoryFrame::Absolute
+                    };
+                }
+                "F5" => {
+                    let view = state.view_state();
+                    state.quicksaves.save(&state.game, view);
+                }
+                "F9" => match state.quicksaves.load() {
+                    Some(Ok((game, view))) => state.restore(game, view),
+                    Some(Err(e)) => web_sys::console::log_1(&e.into()),
+                    None => {}
+                },
+                "F6" => {
+                    state.quicksaves.select_next();
+                    state.quicksaves.show_list = true;
+                }
+                "F7" => state.quicksaves.show_list = !state.quicksaves.show_list,
+                "KeyV" => state.perform(ReplayAction::CycleVessel),
+                "KeyU" => state.perform(ReplayAction::Undock),
+                "F10" => state.toggle_recording(),
+                "F8" => state.toggle_playback(),
+                "KeyG" => state.toggle_ghost(),
+                "KeyK" => state.deterministic = !state.deterministic,
+                "KeyL" => {
+                    // Toggle Lagrange points for the selected planet and its primary
+                    state.lagrange_pair = match state.selected_planet {
+                        Some(secondary) if state.lagrange_pair.map(|(_, s)| s) != Some(secondary) => {
+                            primary_of(&state.game, secondary).map(|primary| (primary, secondary))
+                        }
+                        _ => None,
+                    };
+                }
+                "Digit1" | "Digit2" | "Digit3" | "Digit4" | "Digit5" => {
+                    // Drop a test vessel at the chosen Lagrange point
+                    if let Some((primary, secondary)) = state.lagrange_pair {
+                        let point = key_code[5..].parse::<usize>().unwrap() - 1;
+                        state.perform(ReplayAction::PlaceAtLagrange { primary, secondary, point });
+                    }
+                }
                 _ => {}
             }
         }
     });
 }
 
+/// Serialize the running game; the page keeps it in localStorage or offers it as a download
+#[wasm_bindgen]
+pub fn export_save() -> Result<String, JsValue> {
+    APP_STATE.with(|app| {
+        let app = app.borrow();
+        let state = app.as_ref().ok_or_else(|| JsValue::from_str("Game is not running"))?;
+        save_game(&state.game, state.view_state()).map_err(|e| JsValue::from_str(&e))
+    })
+}
+
+/// Replace the running game with one produced by `export_save`
+#[wasm_bindgen]
+pub fn import_save(text: &str) -> Result<(), JsValue> {
+    let (game, view) = load_game(text).map_err(|e| JsValue::from_str(&e))?;
+    APP_STATE.with(|app| {
+        if let Some(state) = app.borrow_mut().as_mut() {
+            state.restore(game, view);
+        }
+    });
+    Ok(())
+}
+
+/// Serialize the last finished recording, for sharing as a file
+#[wasm_bindgen]
+pub fn export_recording() -> Result<String, JsValue> {
+    APP_STATE.with(|app| {
+        let app = app.borrow();
+        let recording = app
+            .as_ref()
+            .and_then(|state| state.last_recording.as_ref())
+            .ok_or_else(|| JsValue::from_str("No finished recording to export"))?;
+        recording.to_text().map_err(|e| JsValue::from_str(&e))
+    })
+}
+
+/// Play back a recording produced by `export_recording`
+#[wasm_bindgen]
+pub fn play_recording(text: &str) -> Result<(), JsValue> {
+    let recording = Recording::from_text(text).map_err(|e| JsValue::from_str(&e))?;
+    APP_STATE.with(|app| {
+        match app.borrow_mut().as_mut() {
+            Some(state) => state.start_playback(recording).map_err(|e| JsValue::from_str(&e)),
+            None => Err(JsValue::from_str("Game is not running")),
+        }
+    })
+}
+
+/// Race a ghost of a recording produced by `export_recording`
+#[wasm_bindgen]
+pub fn race_recording(text: &str) -> Result<(), JsValue> {
+    let recording = Recording::from_text(text).map_err(|e| JsValue::from_str(&e))?;
+    APP_STATE.with(|app| {
+        match app.borrow_mut().as_mut() {
+            Some(state) => state.start_race(recording).map_err(|e| JsValue::from_str(&e)),
+            None => Err(JsValue::from_str("Game is not running")),
+        }
+    })
+}
+
+/// Predicted trajectories as "csv" or "json", every `decimation`-th step,
+/// relative to the pinned body if there is one
+#[wasm_bindgen]
+pub fn export_ephemeris(format: &str, decimation: usize) -> Result<String, JsValue> {
+    let format = match format {
+        "csv" => EphemerisFormat::Csv,
+        "json" => EphemerisFormat::Json,
+        _ => return Err(JsValue::from_str("Ephemeris format must be csv or json")),
+    };
+    APP_STATE.with(|app| {
+        let app = app.borrow();
+        let state = app.as_ref().ok_or_else(|| JsValue::from_str("Game is not running"))?;
+        let frame = match state.trajectory_frame {
+            TrajectoryFrame::Body(body) => Some(body),
+            _ => None,
+        };
+        let options = EphemerisOptions::new().with_decimation(decimation).relative_to(frame);
+        Ephemeris::from_game(&state.game, &options)
+            .and_then(|ephemeris| ephemeris.to_text(format))
+            .map_err(|e| JsValue::from_str(&e))
+    })
+}
+
+/// Hash of the current simulation state, to compare runs across platforms
+#[wasm_bindgen]
+pub fn state_hash() -> String {
+    APP_STATE.with(|app| {
+        app.borrow()
+            .as_ref()
+            .map(|state| format!("{}:{:016x}", state.game.tick, state.game.state_hash()))
+            .unwrap_or_default()
+    })
+}
+
 #[wasm_bindgen]
 pub fn handle_key_up(key_code: &str) {
     APP_STATE.with(|app| {
@@ -176,6 +492,7 @@ pub fn handle_key_up(key_code: &str) {
                 "KeyA" | "ArrowLeft" => state.input_state.rotate_left = false,
                 "KeyD" | "ArrowRight" => state.input_state.rotate_right = false,
                 "Space" | "KeyW" | "ArrowUp" => state.input_state.thrust = false,
+                "KeyR" => state.input_state.rewind = false,
                 _ => {}
             }
         }
@@ -203,7 +520,7 @@ pub fn handle_mouse_click(x: f64, y: f64, canvas_width: f64, canvas_height: f64)
             state.mouse_pos = (x, y);
 
             // Check if clicking close button on info window
-            if let Some(_) = state.selected_planet {
+            if let Some(planet_idx) = state.selected_planet {
                 let info_x = 50.0;
                 let info_y = 50.0;
                 let close_x = info_x + 280.0;
@@ -213,28 +530,30 @@ pub fn handle_mouse_click(x: f64, y: f64, canvas_width: f64, canvas_height: f64)
                     state.selected_planet = None;
                     return;
                 }
+
+                // Check the info window's action buttons
+                match info_panel_button_at(&state.game, planet_idx, x, y) {
+                    Some(InfoPanelButton::RotatingFrame) => {
+                        // Toggle the frame that rotates with this planet and its primary
+                        state.trajectory_frame = match (state.trajectory_frame, primary_of(&state.game, planet_idx)) {
+                            (TrajectoryFrame::Rotating(_, secondary), _) if secondary == planet_idx => TrajectoryFrame::Relative,
+                            (_, Some(primary)) => TrajectoryFrame::Rotating(primary, planet_idx),
+                            (frame, None) => frame,
+                        };
+                        return;
+                    }
+                    Some(InfoPanelButton::PinReference) => {
+                        state.trajectory_frame = state.trajectory_frame.toggle_pin(planet_idx);
+                        return;
+                    }
+                    None => {}
+                }
             }
 
             // Check if clicking on a planet
-            let center_x = canvas_width / 2.0;
-            let center_y = canvas_height / 2.0;
-            let camera_x = state.game.player.position.x;
-            let camera_y = state.game.player.position.y;
-            let scale = state.zoom_level;
-
-            for (i, planet) in state.game.planets.iter().enumerate() {
-                let screen_x = ((planet.position.x - camera_x) * scale) + center_x;
-                let screen_y = ((planet.position.y - camera_y) * scale) + center_y;
-                let radius = (planet.radius * scale).max(5.0);
-
-                let dx = x - screen_x;
-                let dy = y - screen_y;
-                let dist_sq = dx * dx + dy * dy;
-
-                if dist_sq <= radius * radius {
-                    state.selected_planet = Some(i);
-                    return;
-                }
+            if let Some(i) = planet_at_screen(&state.game, state.zoom_level, canvas_width, canvas_height, x, y) {
+                state.selected_planet = Some(i);
+                return;
             }
 
             // Click elsewhere closes info window
@@ -243,3 +562,17 @@ pub fn handle_mouse_click(x: f64, y: f64, canvas_width: f64, canvas_height: f64)
     });
 }
 
+
+/// Right-click pins a planet as the trajectory reference, or releases it
+#[wasm_bindgen]
+pub fn handle_mouse_right_click(x: f64, y: f64, canvas_width: f64, canvas_height: f64) {
+    APP_STATE.with(|app| {
+        if let Some(state) = app.borrow_mut().as_mut() {
+            state.mouse_pos = (x, y);
+            state.trajectory_frame = match planet_at_screen(&state.game, state.zoom_level, canvas_width, canvas_height, x, y) {
+                Some(i) => state.trajectory_frame.toggle_pin(i),
+                None => TrajectoryFrame::Relative,
+            };
+        }
+    });
+}
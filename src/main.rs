@@ -10,19 +10,45 @@ mod keyboard_input;
 mod texture;
 mod sprite_renderer;
 mod font;
+mod docking;
+mod lagrange;
+mod det_math;
+mod units;
+mod history;
+mod tidal;
+mod flyby;
+mod scenario;
+mod generator;
+mod save;
+mod quicksave;
+mod replay;
+mod ghost;
+mod ephemeris;
+// Shared with the headless simulator, which uses the parts the window does not
+mod offscreen;
+mod capture;
 
 use winit::application::ApplicationHandler;
 use winit::event::{WindowEvent, MouseScrollDelta, ElementState, MouseButton};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::window::{Window, WindowId};
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
 use std::sync::Arc;
 use std::time::Instant;
 use softbuffer::{Context, Surface};
-use crate::game::{Game, TRAJECTORY_DT};
-use crate::render::render_game;
-use crate::initial_universe::create_universe;
+use crate::capture::{Capture, CaptureOptions, DEFAULT_CAPTURE_PATH};
+use crate::ephemeris::{Ephemeris, EphemerisFormat, EphemerisOptions, DEFAULT_EPHEMERIS_PATH};
+use crate::game::Game;
+use crate::ghost::Ghost;
+use crate::render::{info_panel_button_at, planet_at_screen, render_game, Camera, InfoPanelButton, Overlay, TrajectoryFrame};
+use crate::initial_universe::{create_universe, scenario_by_name};
 use crate::keyboard_input::InputState;
+use crate::lagrange::primary_of;
+use crate::offscreen::{MIN_RENDER_HEIGHT, MIN_RENDER_WIDTH};
+use crate::quicksave::Quicksaves;
+use crate::replay::{FrameInput, Playback, Recording, ReplayAction, DEFAULT_RECORDING_PATH, SEEK_SECONDS};
+use crate::save::{load_game_file, save_game_file, ViewState, DEFAULT_SAVE_PATH};
+use crate::scenario::load_scenario_file;
 
 struct App {
     window: Option<Arc<Window>>,
@@ -33,9 +59,213 @@ struct App {
     time_accumulator: f64,
     zoom_level: f64,
     time_warp: f64,
-    show_absolute_trajectories: bool,
+    trajectory_frame: TrajectoryFrame,
     selected_planet: Option<usize>,
+    lagrange_pair: Option<(usize, usize)>,
+    deterministic: bool,
     mouse_pos: (f64, f64),
+    modifiers: ModifiersState,
+    quicksaves: Quicksaves,
+    recording: Option<Recording>,
+    last_recording: Option<Recording>,
+    playback: Option<Playback>,
+    ghost: Option<Ghost>,
+    capture: Option<Capture>,
+    scenario: String,
+}
+
+impl App {
+    fn view_state(&self) -> ViewState {
+        ViewState {
+            zoom_level: self.zoom_level,
+            time_warp: self.time_warp,
+            selected_planet: self.selected_planet,
+        }
+    }
+
+    /// Switch to a restored game and view, ending any recording or replay of the old one
+    fn restore(&mut self, game: Game, view: ViewState) {
+        self.stop_recording();
+        if let Some(playback) = self.playback.take() {
+            self.last_recording = Some(playback.into_recording());
+        }
+        self.ghost = None;
+        self.game = Some(game);
+        self.zoom_level = view.zoom_level;
+        self.time_warp = view.time_warp;
+        self.selected_planet = view.selected_planet;
+        self.time_accumulator = 0.0;
+        // Frames and Lagrange pairs refer to body indices that may have changed
+        self.trajectory_frame = TrajectoryFrame::Relative;
+        self.lagrange_pair = None;
+    }
+
+    fn save_to(&self, path: &str) {
+        let Some(game) = &self.game else {
+            return;
+        };
+        match save_game_file(path, game, self.view_state()) {
+            Ok(()) => println!("Saved game to {}", path),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    /// Apply a command to the game, recording it if a recording is running
+    fn perform(&mut self, action: ReplayAction) {
+        if let Some(game) = &mut self.game {
+            if let Some(recording) = &mut self.recording {
+                recording.push_action(action);
+            }
+            action.apply(game);
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            match recording.save_file(DEFAULT_RECORDING_PATH) {
+                Ok(()) => println!("Saved {:.1} s recording to {}", recording.duration(), DEFAULT_RECORDING_PATH),
+                Err(e) => eprintln!("{}", e),
+            }
+            self.last_recording = Some(recording);
+        }
+    }
+
+    fn toggle_recording(&mut self) {
+        if self.recording.is_some() {
+            self.stop_recording();
+            return;
+        }
+        let view = self.view_state();
+        if let Some(game) = &mut self.game {
+            self.recording = Some(Recording::start(game, view, self.time_accumulator));
+        }
+    }
+
+    /// Restart from a recording's first frame and play it back, returning whether it started
+    fn start_playback(&mut self, recording: Recording) -> bool {
+        match Playback::start(recording) {
+            Ok((playback, game, view)) => {
+                self.restore(game, view);
+                self.playback = Some(playback);
+                true
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                false
+            }
+        }
+    }
+
+    /// Play the last recording, or the one on disk; stopping hands control back mid-flight
+    fn toggle_playback(&mut self) {
+        if let Some(playback) = self.playback.take() {
+            self.last_recording = Some(playback.into_recording());
+            return;
+        }
+        self.stop_recording();
+        let recording = match self.last_recording.take() {
+            Some(recording) => recording,
+            None => match Recording::load_file(DEFAULT_RECORDING_PATH) {
+                Ok(recording) => recording,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            },
+        };
+        self.start_playback(recording);
+    }
+
+    /// Race a ghost of the last recording from its start, or call the race off
+    fn toggle_ghost(&mut self) {
+        if self.ghost.take().is_some() {
+            return;
+        }
+        self.stop_recording();
+        let recording = match &self.last_recording {
+            Some(recording) => recording.clone(),
+            None => match Recording::load_file(DEFAULT_RECORDING_PATH) {
+                Ok(recording) => recording,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            },
+        };
+        match Ghost::race(recording, self.selected_planet) {
+            Ok((ghost, game, view)) => {
+                self.restore(game, view);
+                self.ghost = Some(ghost);
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    /// Write the predicted trajectories as CSV and JSON, relative to the pinned body if there is one
+    fn export_ephemeris(&self) {
+        let Some(game) = &self.game else {
+            return;
+        };
+        let frame = match self.trajectory_frame {
+            TrajectoryFrame::Body(body) => Some(body),
+            _ => None,
+        };
+        let ephemeris = match Ephemeris::from_game(game, &EphemerisOptions::new().relative_to(frame)) {
+            Ok(ephemeris) => ephemeris,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+        for format in [EphemerisFormat::Csv, EphemerisFormat::Json] {
+            let path = format!("{}.{}", DEFAULT_EPHEMERIS_PATH, format.extension());
+            match ephemeris.save_file(&path) {
+                Ok(()) => println!("Exported {} ephemeris samples to {}", ephemeris.time.len(), path),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    }
+
+    fn stop_capture(&mut self) {
+        if let Some(capture) = self.capture.take() {
+            match capture.finish() {
+                Ok(summary) => println!("{}", summary),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    }
+
+    /// Capture every few frames as an animated GIF at the window's size, following the view as it changes
+    fn toggle_capture(&mut self) {
+        if self.capture.is_some() {
+            self.stop_capture();
+            return;
+        }
+        let Some(window) = &self.window else {
+            return;
+        };
+        let size = window.inner_size();
+        let (width, height) = ((size.width as usize).max(MIN_RENDER_WIDTH), (size.height as usize).max(MIN_RENDER_HEIGHT));
+        match Capture::start(DEFAULT_CAPTURE_PATH, width, height, CaptureOptions::new()) {
+            Ok(capture) => self.capture = Some(capture),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    /// Replace the running game with a saved one, returning whether it loaded
+    fn load_from(&mut self, path: &str) -> bool {
+        match load_game_file(path) {
+            Ok((game, view)) => {
+                self.restore(game, view);
+                println!("Loaded game from {}", path);
+                true
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                false
+            }
+        }
+    }
 }
 
 impl ApplicationHandler for App {
@@ -49,12 +279,20 @@ impl ApplicationHandler for App {
             let context = Context::new(window.clone()).unwrap();
             let surface = Surface::new(&context, window.clone()).unwrap();
 
-            // Initialize game universe
-            let game = create_universe();
+            // Initialize game universe from a save, a recording to replay, or a scenario
+            let scenario = self.scenario.clone();
+            let restored = (scenario.ends_with(".sav") && self.load_from(&scenario))
+                || (scenario.ends_with(".rec") && Recording::load_file(&scenario).map_err(|e| eprintln!("{}", e)).is_ok_and(|recording| self.start_playback(recording)));
+            if !restored {
+                let game = load_game(&scenario).unwrap_or_else(|e| {
+                    eprintln!("{}, using default", e);
+                    create_universe()
+                });
+                self.game = Some(game);
+            }
 
             self.surface = Some(surface);
             self.window = Some(window);
-            self.game = Some(game);
             self.last_update = Some(Instant::now());
 
             // Trigger initial render
@@ -67,11 +305,52 @@ impl ApplicationHandler for App {
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
         match event {
             WindowEvent::CloseRequested => {
+                // Let the capture finish its file before the process ends
+                self.stop_capture();
                 event_loop.exit();
             }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
             WindowEvent::KeyboardInput { event, .. } => {
                 self.input_state.handle_key_event(&event);
 
+                // Replay controls take over keys, and anything that would change the replayed game is ignored
+                if event.state == ElementState::Pressed
+                    && let (Some(playback), Some(game)) = (&mut self.playback, &mut self.game)
+                {
+                    let handled = match event.physical_key {
+                        PhysicalKey::Code(KeyCode::KeyP) => {
+                            playback.paused = !playback.paused;
+                            true
+                        }
+                        PhysicalKey::Code(KeyCode::Period) => {
+                            playback.faster();
+                            true
+                        }
+                        PhysicalKey::Code(KeyCode::Comma) => {
+                            playback.slower();
+                            true
+                        }
+                        PhysicalKey::Code(KeyCode::ArrowRight) => {
+                            playback.seek(game, SEEK_SECONDS);
+                            true
+                        }
+                        PhysicalKey::Code(KeyCode::ArrowLeft) => {
+                            playback.seek(game, -SEEK_SECONDS);
+                            true
+                        }
+                        PhysicalKey::Code(
+                            KeyCode::KeyV | KeyCode::KeyU | KeyCode::KeyK | KeyCode::KeyO | KeyCode::F5 | KeyCode::F9 | KeyCode::F10 | KeyCode::KeyG
+                            | KeyCode::Digit1 | KeyCode::Digit2 | KeyCode::Digit3 | KeyCode::Digit4 | KeyCode::Digit5,
+                        ) => true,
+                        _ => false,
+                    };
+                    if handled {
+                        return;
+                    }
+                }
+
                 // Handle zoom, time warp, and display mode keys
                 if event.state == ElementState::Pressed {
                     match event.physical_key {
@@ -90,7 +369,87 @@ impl ApplicationHandler for App {
                             self.time_warp = self.time_warp.max(1.0);
                         }
                         PhysicalKey::Code(KeyCode::Tab) => {
-                            self.show_absolute_trajectories = !self.show_absolute_trajectories;
+                            self.trajectory_frame = if self.trajectory_frame == TrajectoryFrame::Absolute {
+                                TrajectoryFrame::Relative
+                            } else {
+                                TrajectoryFrame::Absolute
+                            };
+                        }
+                        PhysicalKey::Code(KeyCode::KeyS) if self.modifiers.control_key() => {
+                            self.save_to(DEFAULT_SAVE_PATH);
+                        }
+                        PhysicalKey::Code(KeyCode::KeyO) if self.modifiers.control_key() => {
+                            self.load_from(DEFAULT_SAVE_PATH);
+                        }
+                        PhysicalKey::Code(KeyCode::F5) => {
+                            let view = self.view_state();
+                            if let Some(game) = &self.game {
+                                self.quicksaves.save(game, view);
+                            }
+                        }
+                        PhysicalKey::Code(KeyCode::F9) => match self.quicksaves.load() {
+                            Some(Ok((game, view))) => self.restore(game, view),
+                            Some(Err(e)) => eprintln!("{}", e),
+                            None => {}
+                        },
+                        PhysicalKey::Code(KeyCode::F6) => {
+                            self.quicksaves.select_next();
+                            self.quicksaves.show_list = true;
+                        }
+                        PhysicalKey::Code(KeyCode::F7) => {
+                            self.quicksaves.show_list = !self.quicksaves.show_list;
+                        }
+                        PhysicalKey::Code(KeyCode::KeyV) => {
+                            self.perform(ReplayAction::CycleVessel);
+                        }
+                        PhysicalKey::Code(KeyCode::KeyU) => {
+                            self.perform(ReplayAction::Undock);
+                        }
+                        PhysicalKey::Code(KeyCode::F10) => {
+                            // Start recording the flight, or stop and write it out
+                            self.toggle_recording();
+                        }
+                        PhysicalKey::Code(KeyCode::F8) => {
+                            // Replay the last recording, or stop replaying and take over
+                            self.toggle_playback();
+                        }
+                        PhysicalKey::Code(KeyCode::KeyG) => {
+                            // Race a ghost of the last recording, or stop racing
+                            self.toggle_ghost();
+                        }
+                        PhysicalKey::Code(KeyCode::KeyC) => {
+                            // Start or stop capturing an animation of the flight
+                            self.toggle_capture();
+                        }
+                        PhysicalKey::Code(KeyCode::KeyE) => {
+                            // Export the predicted trajectories for analysis
+                            self.export_ephemeris();
+                        }
+                        PhysicalKey::Code(KeyCode::KeyK) => {
+                            // Toggle fixed-tick deterministic input
+                            self.deterministic = !self.deterministic;
+                        }
+                        PhysicalKey::Code(KeyCode::KeyL) => {
+                            // Toggle Lagrange points for the selected planet and its primary
+                            self.lagrange_pair = match (self.selected_planet, &self.game) {
+                                (Some(secondary), Some(game)) if self.lagrange_pair.map(|(_, s)| s) != Some(secondary) => {
+                                    primary_of(game, secondary).map(|primary| (primary, secondary))
+                                }
+                                _ => None,
+                            };
+                        }
+                        PhysicalKey::Code(code @ (KeyCode::Digit1 | KeyCode::Digit2 | KeyCode::Digit3 | KeyCode::Digit4 | KeyCode::Digit5)) => {
+                            // Drop a test vessel at the chosen Lagrange point
+                            if let Some((primary, secondary)) = self.lagrange_pair {
+                                let point = match code {
+                                    KeyCode::Digit1 => 0,
+                                    KeyCode::Digit2 => 1,
+                                    KeyCode::Digit3 => 2,
+                                    KeyCode::Digit4 => 3,
+                                    _ => 4,
+                                };
+                                self.perform(ReplayAction::PlaceAtLagrange { primary, secondary, point });
+                            }
                         }
                         _ => {}
                     }
@@ -126,7 +485,7 @@ impl ApplicationHandler for App {
                     let height = size.height as f64;
 
                     // Check if clicking close button on info window
-                    if let Some(_planet_idx) = self.selected_planet {
+                    if let Some(planet_idx) = self.selected_planet {
                         let info_x = 50.0;
                         let info_y = 50.0;
                         let close_x = info_x + 280.0;
@@ -137,34 +496,46 @@ impl ApplicationHandler for App {
                             self.selected_planet = None;
                             return;
                         }
+
+                        // Check the info window's action buttons
+                        match info_panel_button_at(game, planet_idx, self.mouse_pos.0, self.mouse_pos.1) {
+                            Some(InfoPanelButton::RotatingFrame) => {
+                                // Toggle the frame that rotates with this planet and its primary
+                                self.trajectory_frame = match (self.trajectory_frame, primary_of(game, planet_idx)) {
+                                    (TrajectoryFrame::Rotating(_, secondary), _) if secondary == planet_idx => TrajectoryFrame::Relative,
+                                    (_, Some(primary)) => TrajectoryFrame::Rotating(primary, planet_idx),
+                                    (frame, None) => frame,
+                                };
+                                return;
+                            }
+                            Some(InfoPanelButton::PinReference) => {
+                                self.trajectory_frame = self.trajectory_frame.toggle_pin(planet_idx);
+                                return;
+                            }
+                            None => {}
+                        }
                     }
 
                     // Check if clicking on a planet
-                    let center_x = width / 2.0;
-                    let center_y = height / 2.0;
-                    let camera_x = game.player.position.x;
-                    let camera_y = game.player.position.y;
-                    let scale = self.zoom_level;
-
-                    for (i, planet) in game.planets.iter().enumerate() {
-                        let screen_x = ((planet.position.x - camera_x) * scale) + center_x;
-                        let screen_y = ((planet.position.y - camera_y) * scale) + center_y;
-                        let radius = (planet.radius * scale).max(5.0);
-
-                        let dx = self.mouse_pos.0 - screen_x;
-                        let dy = self.mouse_pos.1 - screen_y;
-                        let dist_sq = dx * dx + dy * dy;
-
-                        if dist_sq <= radius * radius {
-                            self.selected_planet = Some(i);
-                            return;
-                        }
+                    if let Some(i) = planet_at_screen(game, self.zoom_level, width, height, self.mouse_pos.0, self.mouse_pos.1) {
+                        self.selected_planet = Some(i);
+                        return;
                     }
 
                     // Click elsewhere closes info window
                     self.selected_planet = None;
                 }
             }
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Right, .. } => {
+                // Right-click pins a planet as the trajectory reference, or releases it
+                if let (Some(window), Some(game)) = (&self.window, &self.game) {
+                    let size = window.inner_size();
+                    self.trajectory_frame = match planet_at_screen(game, self.zoom_level, size.width as f64, size.height as f64, self.mouse_pos.0, self.mouse_pos.1) {
+                        Some(i) => self.trajectory_frame.toggle_pin(i),
+                        None => TrajectoryFrame::Relative,
+                    };
+                }
+            }
             WindowEvent::RedrawRequested => {
                 // Update game state
                 if let (Some(game), Some(last_update)) = (&mut self.game, &mut self.last_update) {
@@ -172,23 +543,17 @@ impl ApplicationHandler for App {
                     let dt = now.duration_since(*last_update).as_secs_f64();
                     *last_update = now;
 
-                    // Apply input to game (this will recalculate trajectory if input changed)
-                    self.input_state.apply_to_game(game, dt);
-
-                    // Accumulate time with time warp multiplier and advance trajectory steps
-                    self.time_accumulator += dt * self.time_warp;
-
-                    let steps_to_advance = (self.time_accumulator / TRAJECTORY_DT) as usize;
-                    if steps_to_advance > 0 {
-                        // Advance multiple steps at once
-                        for _ in 0..steps_to_advance {
-                            game.advance_trajectory();
+                    if let Some(playback) = &mut self.playback {
+                        // Replays run on their own recorded clock and controls
+                        playback.update(game);
+                    } else {
+                        if let Some(recording) = &mut self.recording {
+                            recording.push_frame(FrameInput::capture(&self.input_state, dt, self.time_warp, self.deterministic));
+                        }
+                        self.input_state.advance_frame(game, &mut self.time_accumulator, dt, self.time_warp, self.deterministic);
+                        if let Some(ghost) = &mut self.ghost {
+                            ghost.update(game);
                         }
-
-                        // Batch extend trajectories to maintain look-ahead
-                        game.extend_trajectories(steps_to_advance);
-
-                        self.time_accumulator -= steps_to_advance as f64 * TRAJECTORY_DT;
                     }
                 }
 
@@ -212,7 +577,41 @@ impl ApplicationHandler for App {
 
                     let mut buffer = surface.buffer_mut().unwrap();
 
-                    render_game(&mut buffer, width, height, game, self.input_state.thrust, self.zoom_level, self.time_warp, self.show_absolute_trajectories, self.selected_planet);
+                    // Show the replay's controls and warp while one is playing
+                    let (input_state, time_warp, replay_status) = match (&self.playback, &self.recording) {
+                        (Some(playback), _) => (playback.input_state(), playback.time_warp(), Some(playback.status())),
+                        (None, Some(recording)) => (&self.input_state, self.time_warp, Some(format!("REC {:.1} s", recording.duration()))),
+                        (None, None) => (&self.input_state, self.time_warp, None),
+                    };
+
+                    let camera = Camera {
+                        width,
+                        height,
+                        zoom_level: self.zoom_level,
+                        frame: self.trajectory_frame,
+                        selected_planet: self.selected_planet,
+                        lagrange_pair: self.lagrange_pair,
+                        time_warp,
+                        thrusting: input_state.thrust,
+                        deterministic: self.deterministic,
+                    };
+
+                    // Capture what is on screen, without the capture's own status
+                    if let Some(capture) = &mut self.capture
+                        && capture.next_frame()
+                        && let Err(e) = capture.write(game, &camera)
+                    {
+                        eprintln!("{}", e);
+                    }
+                    let replay_status = replay_status.or_else(|| self.capture.as_ref().map(|capture| format!("CAPTURE {} frames", capture.frames_written)));
+
+                    let overlay = Overlay {
+                        rewinding: input_state.rewind,
+                        quicksaves: Some(&self.quicksaves),
+                        replay_status: replay_status.as_deref(),
+                        ghost: self.ghost.as_ref(),
+                    };
+                    render_game(&mut buffer, game, &camera, &overlay);
 
                     buffer.present().unwrap();
                 }
@@ -227,6 +626,15 @@ impl ApplicationHandler for App {
     }
 }
 
+/// Load a scenario file if given a .toml path, otherwise a built-in scenario by name
+fn load_game(scenario: &str) -> Result<Game, String> {
+    if scenario.ends_with(".toml") {
+        load_scenario_file(scenario)
+    } else {
+        scenario_by_name(scenario).ok_or_else(|| format!("Unknown scenario '{}'", scenario))
+    }
+}
+
 fn main() {
     let event_loop = EventLoop::new().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
@@ -240,9 +648,19 @@ fn main() {
         time_accumulator: 0.0,
         zoom_level: 1.0,
         time_warp: 1.0,
-        show_absolute_trajectories: false, // Start with planet-relative mode
+        trajectory_frame: TrajectoryFrame::Relative, // Start with planet-relative mode
         selected_planet: None,
+        lagrange_pair: None,
+        deterministic: false,
         mouse_pos: (0.0, 0.0),
+        modifiers: ModifiersState::empty(),
+        quicksaves: Quicksaves::new(),
+        recording: None,
+        last_recording: None,
+        playback: None,
+        ghost: None,
+        capture: None,
+        scenario: std::env::args().nth(1).unwrap_or_else(|| "default".to_string()),
     };
 
     event_loop.run_app(&mut app).unwrap();
@@ -0,0 +1,152 @@
+//! Rendering without a window, for still images and regression tests
+
+use crate::ephemeris::body_by_name;
+use crate::game::Game;
+use crate::render::{render_game, Camera, Overlay, TrajectoryFrame};
+use image::RgbImage;
+
+// Smallest image the HUD fits in; render_game lays text out from the right edge
+pub const MIN_RENDER_WIDTH: usize = 400;
+pub const MIN_RENDER_HEIGHT: usize = 300;
+
+#[allow(dead_code)]
+pub const DEFAULT_RENDER_WIDTH: usize = 1200;
+#[allow(dead_code)]
+pub const DEFAULT_RENDER_HEIGHT: usize = 800;
+
+// Built up for offscreen renders; the window fills in its own
+impl Camera {
+    /// The view a new window starts with, at the given resolution
+    #[allow(dead_code)]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            zoom_level: 1.0,
+            frame: TrajectoryFrame::Relative,
+            selected_planet: None,
+            lagrange_pair: None,
+            time_warp: 1.0,
+            thrusting: false,
+            deterministic: false,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_zoom(mut self, zoom_level: f64) -> Self {
+        self.zoom_level = zoom_level;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_frame(mut self, frame: TrajectoryFrame) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_selected_planet(mut self, planet: usize) -> Self {
+        self.selected_planet = Some(planet);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_lagrange_pair(mut self, primary: usize, secondary: usize) -> Self {
+        self.lagrange_pair = Some((primary, secondary));
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_time_warp(mut self, time_warp: f64) -> Self {
+        self.time_warp = time_warp;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_thrust(mut self, thrusting: bool) -> Self {
+        self.thrusting = thrusting;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Check the camera fits the game before anything indexes with it
+    fn validate(&self, game: &Game) -> Result<(), String> {
+        if self.width < MIN_RENDER_WIDTH || self.height < MIN_RENDER_HEIGHT {
+            return Err(format!("Render size must be at least {}x{}", MIN_RENDER_WIDTH, MIN_RENDER_HEIGHT));
+        }
+        if self.zoom_level.is_nan() || self.zoom_level <= 0.0 {
+            return Err("Zoom must be positive".to_string());
+        }
+        let bodies = game.planets.len();
+        let frame_bodies = match self.frame {
+            TrajectoryFrame::Absolute | TrajectoryFrame::Relative => vec![],
+            TrajectoryFrame::Rotating(primary, secondary) => vec![primary, secondary],
+            TrajectoryFrame::Body(reference) => vec![reference],
+        };
+        let pair = self.lagrange_pair.map(|(primary, secondary)| vec![primary, secondary]).unwrap_or_default();
+        if let Some(body) = frame_bodies.into_iter().chain(pair).chain(self.selected_planet).find(|&body| body >= bodies) {
+            return Err(format!("Camera refers to body {} but the game has {}", body, bodies));
+        }
+        Ok(())
+    }
+}
+
+/// Render one frame into a new buffer of 0xRRGGBB pixels, as the window would show it
+pub fn render_frame(game: &Game, camera: &Camera) -> Result<Vec<u32>, String> {
+    camera.validate(game)?;
+    let mut buffer = vec![0; camera.width * camera.height];
+    render_game(&mut buffer, game, camera, &Overlay::default());
+    Ok(buffer)
+}
+
+/// Convert a render buffer to an image
+pub fn to_image(buffer: &[u32], width: usize, height: usize) -> RgbImage {
+    RgbImage::from_fn(width as u32, height as u32, |x, y| {
+        let pixel = buffer[y as usize * width + x as usize];
+        image::Rgb([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+    })
+}
+
+pub fn render_image(game: &Game, camera: &Camera) -> Result<RgbImage, String> {
+    Ok(to_image(&render_frame(game, camera)?, camera.width, camera.height))
+}
+
+/// Render one frame to an image file; the format follows the extension, usually .png
+#[allow(dead_code)]
+pub fn render_to_file(game: &Game, camera: &Camera, path: &str) -> Result<(), String> {
+    render_image(game, camera)?.save(path).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Parse a resolution such as "1200x800"
+#[allow(dead_code)]
+pub fn parse_size(text: &str) -> Result<(usize, usize), String> {
+    let (width, height) = text.split_once('x').ok_or_else(|| format!("Size '{}' must look like 1200x800", text))?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => Err(format!("Size '{}' must look like 1200x800", text)),
+    }
+}
+
+/// Parse a trajectory frame by name, with bodies named as in the game
+#[allow(dead_code)]
+pub fn parse_frame(game: &Game, text: &str) -> Result<TrajectoryFrame, String> {
+    let body = |name: &str| body_by_name(game, name).ok_or_else(|| format!("No body named '{}'", name));
+    match text {
+        "absolute" => Ok(TrajectoryFrame::Absolute),
+        "relative" => Ok(TrajectoryFrame::Relative),
+        _ => {
+            if let Some(name) = text.strip_prefix("body:") {
+                Ok(TrajectoryFrame::Body(body(name)?))
+            } else if let Some((primary, secondary)) = text.strip_prefix("rotating:").and_then(|pair| pair.split_once(',')) {
+                Ok(TrajectoryFrame::Rotating(body(primary)?, body(secondary)?))
+            } else {
+                Err(format!("Unknown frame '{}' (expected absolute, relative, body:<name> or rotating:<primary>,<secondary>)", text))
+            }
+        }
+    }
+}
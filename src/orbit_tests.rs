@@ -0,0 +1,91 @@
+//! Orbits set up from elements come out with the elements asked for.
+
+use crate::game::{Game, DEFAULT_BIG_GRAVITY};
+use crate::generator::{generate_system, GeneratorSettings};
+use crate::initial_universe::calculate_elliptical_orbit;
+use crate::planet::{BodyRole, Planet};
+use crate::player::Player;
+use crate::scenario::load_scenario;
+use crate::vector2::Vector2;
+
+/// Osculating semi-major axis and eccentricity of a body around a much heavier parent
+fn elements(game: &Game, body: usize, parent: usize) -> (f64, f64) {
+    let (b, p) = (&game.planets[body], &game.planets[parent]);
+    let mu = game.big_gravity * p.mass;
+    let r = b.position.subtract(&p.position);
+    let v = b.velocity.subtract(&p.velocity);
+    let energy = v.dot(&v) / 2.0 - mu / r.magnitude();
+    let h = r.cross(&v);
+    (-mu / (2.0 * energy), (1.0 + 2.0 * energy * h * h / (mu * mu)).max(0.0).sqrt())
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() <= 1e-9 * expected.abs().max(1.0), "{} is not {}", actual, expected);
+}
+
+#[test]
+fn scenario_orbits_have_their_elements() {
+    let source = r#"
+        [[bodies]]
+        name = "Star"
+        radius = 300.0
+        mass = 1e15
+        position = [0.0, 0.0]
+        velocity = [0.0, 0.0]
+        color = 0xFFFF00
+
+        [[bodies]]
+        name = "Far"
+        radius = 10.0
+        mass = 1.0
+        orbit = { parent = "Star", semi_major_axis = 20000.0, eccentricity = 0.3, argument_of_periapsis = 40.0 }
+        color = 0xFFFFFF
+
+        [[bodies]]
+        name = "Near"
+        radius = 10.0
+        mass = 1.0
+        orbit = { parent = "Star", periapsis = 5000.0, eccentricity = 0.6 }
+        color = 0xFFFFFF
+
+        [[vessels]]
+        mass = 1.0
+        orbit = { parent = "Star", periapsis = 1000.0 }
+    "#;
+    let game = load_scenario(source, |_| Ok(None), |path| Err(format!("no table {}", path))).expect("scenario is valid");
+
+    let (a, e) = elements(&game, 1, 0);
+    assert_close(a, 20000.0);
+    assert_close(e, 0.3);
+
+    let (a, e) = elements(&game, 2, 0);
+    assert_close(a * (1.0 - e), 5000.0);
+    assert_close(e, 0.6);
+}
+
+#[test]
+fn elliptical_orbits_have_their_semi_major_axis() {
+    let origin = Vector2 { x: 0.0, y: 0.0 };
+    let star = Planet::new("Star".to_string(), 300.0, 1e15, origin, origin, 0xFFFF00);
+    let (position, velocity) = calculate_elliptical_orbit(origin, origin, star.mass, 30000.0, 0.04, 2.0, DEFAULT_BIG_GRAVITY);
+    let planet = Planet::new("Planet".to_string(), 10.0, 1.0, position, velocity, 0xFFFFFF);
+    let vessel = Player::new(Vector2 { x: 0.0, y: 1000.0 }, origin, 1.0, 0.0);
+    let game = Game::new(vec![star, planet], vec![vessel]);
+
+    let (a, e) = elements(&game, 1, 0);
+    assert_close(a, 30000.0);
+    assert_close(e, 0.04);
+}
+
+#[test]
+fn generated_planets_start_at_periapsis_of_a_near_circular_orbit() {
+    for seed in 0..10 {
+        let game = generate_system(&GeneratorSettings::with_seed(seed));
+        let star = game.planets.iter().position(|planet| planet.role == BodyRole::Star).expect("system has a star");
+        for (i, planet) in game.planets.iter().enumerate().filter(|(_, planet)| planet.role == BodyRole::Planet) {
+            let (a, e) = elements(&game, i, star);
+            assert!(e < 0.05, "seed {} {} has eccentricity {}", seed, planet.name, e);
+            assert_close(a * (1.0 - e), planet.position.distance(&game.planets[star].position));
+        }
+    }
+}
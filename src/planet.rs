@@ -1,7 +1,38 @@
 use crate::vector2::Vector2;
 use crate::texture::Texture;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+/// What a body is in its system, used to pick reference frames instead of Vec order
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BodyRole {
+    Star,
+    Planet,
+    Moon,
+    Barycenter, // Massless marker that coasts at constant velocity and anchors a multi-star system
+}
+
+impl BodyRole {
+    /// Position in the hierarchy; bodies orbit bodies of a higher rank
+    pub fn rank(&self) -> u8 {
+        match self {
+            BodyRole::Moon => 0,
+            BodyRole::Planet => 1,
+            BodyRole::Star => 2,
+            BodyRole::Barycenter => 3,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BodyRole::Star => "Star",
+            BodyRole::Planet => "Planet",
+            BodyRole::Moon => "Moon",
+            BodyRole::Barycenter => "Barycenter",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Planet {
     pub name: String,
     pub radius: f64,
@@ -9,13 +40,19 @@ pub struct Planet {
     pub position: Vector2,
     pub velocity: Vector2,
     pub color: u32, // RGB color (0xRRGGBB)
+    #[serde(skip)]
     pub texture: Option<Texture>,
+    pub texture_source: Option<String>, // Where the texture came from, so saves can store it by reference
     pub description: String,
+    pub luminosity: f64, // Radiation pressure on a unit area at unit distance; zero for dark bodies
+    pub role: BodyRole,
+    #[serde(default)]
+    pub fresh_fragment: bool, // Just torn off; not disrupted again until it has left the Roche limit once
 }
 
 impl Planet {
     pub fn new(name: String, radius: f64, mass: f64, position: Vector2, velocity: Vector2, color: u32) -> Self {
-        Self { name, radius, mass, position, velocity, color, texture: None, description: String::new() }
+        Self { name, radius, mass, position, velocity, color, texture: None, texture_source: None, description: String::new(), luminosity: 0.0, role: BodyRole::Planet, fresh_fragment: false }
     }
 
     pub fn with_texture(mut self, texture: Texture) -> Self {
@@ -23,8 +60,23 @@ impl Planet {
         self
     }
 
+    pub fn with_texture_source(mut self, source: String) -> Self {
+        self.texture_source = Some(source);
+        self
+    }
+
     pub fn with_description(mut self, description: String) -> Self {
         self.description = description;
         self
     }
+
+    pub fn with_luminosity(mut self, luminosity: f64) -> Self {
+        self.luminosity = luminosity;
+        self
+    }
+
+    pub fn with_role(mut self, role: BodyRole) -> Self {
+        self.role = role;
+        self
+    }
 }
\ No newline at end of file
@@ -1,15 +1,100 @@
+use crate::det_math;
+use crate::docking::{DockedVessel, DockingPort};
 use crate::vector2::Vector2;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy)]
+/// Distance from the ship's center to its hull, used so the ship rests on
+/// the surface instead of sinking its center into it
+pub const PLAYER_FOOTPRINT: f64 = 8.0;
+
+/// Engine thrust of a standard vessel
+pub const DEFAULT_ENGINE_THRUST: f64 = 25.0;
+
+/// Fastest descent along the surface normal that still lands, as a fraction of
+/// the circular orbit speed at the surface; anything faster is a crash
+pub const CRASH_SPEED_FRACTION: f64 = 0.25;
+
+/// Resting contact with a planet's surface
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct SurfaceContact {
+    pub planet_index: usize,
+    pub normal: Vector2, // Unit vector from the planet center to the contact point
+    #[serde(default)]
+    pub crashed: bool, // Came down too fast; the wreck stays on the surface for good
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Player {
     pub position: Vector2,
     pub velocity: Vector2,
     pub mass: f64,
     pub rotation: f64,
+    pub landed: Option<SurfaceContact>,
+    pub port: DockingPort,
+    pub docked: Option<DockedVessel>, // At most one; a docked pair can't take on a third vessel
+    #[serde(default)]
+    pub undock_latched: bool, // Just undocked; the port can't capture until it has cleared every other port
+    pub engine_thrust: f64,
+    pub sail_area_to_mass: f64, // Zero for vessels without a sail
 }
 
 impl Player {
     pub fn new(position: Vector2, velocity: Vector2, mass: f64, rotation: f64) -> Self {
-        Self { position, velocity, mass, rotation }
+        Self {
+            position,
+            velocity,
+            mass,
+            rotation,
+            landed: None,
+            port: DockingPort::nose(),
+            docked: None,
+            undock_latched: false,
+            engine_thrust: DEFAULT_ENGINE_THRUST,
+            sail_area_to_mass: 0.0,
+        }
+    }
+
+    /// Fit a solar sail, facing along the nose
+    pub fn with_sail(mut self, area_to_mass: f64) -> Self {
+        self.sail_area_to_mass = area_to_mass;
+        self
+    }
+
+    pub fn with_engine_thrust(mut self, engine_thrust: f64) -> Self {
+        self.engine_thrust = engine_thrust;
+        self
     }
-}
\ No newline at end of file
+
+    /// Radiation pressure acceleration from a luminous body. The sail is a flat
+    /// reflector whose normal is the nose direction; the push is along that
+    /// normal and scales with the cosine squared of the incidence angle.
+    pub fn sail_acceleration(&self, source_position: &Vector2, luminosity: f64) -> Vector2 {
+        if self.sail_area_to_mass == 0.0 || luminosity == 0.0 {
+            return Vector2 { x: 0.0, y: 0.0 };
+        }
+
+        let diff = self.position.subtract(source_position);
+        let distance_sq = diff.dot(&diff);
+        if distance_sq == 0.0 {
+            return Vector2 { x: 0.0, y: 0.0 };
+        }
+        let sunlight = diff.scale(1.0 / distance_sq.sqrt());
+
+        // Two-sided sail: always push away from the source
+        let mut normal = Vector2 { x: det_math::sin(self.rotation), y: -det_math::cos(self.rotation) };
+        let mut cos_incidence = normal.dot(&sunlight);
+        if cos_incidence < 0.0 {
+            normal = normal.scale(-1.0);
+            cos_incidence = -cos_incidence;
+        }
+
+        normal.scale(luminosity * self.sail_area_to_mass * cos_incidence * cos_incidence / distance_sq)
+    }
+}
+
+impl SurfaceContact {
+    /// Rotation that points the ship's nose along the surface normal
+    pub fn rotation(&self) -> f64 {
+        det_math::atan2(self.normal.x, -self.normal.y)
+    }
+}
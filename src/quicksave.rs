@@ -0,0 +1,105 @@
+//! In-memory quicksave slots, restored without decoding textures again
+
+use crate::game::{Game, TRAJECTORY_DT};
+use crate::save::{SaveFile, ViewState};
+use crate::texture::Texture;
+use std::collections::HashMap;
+
+#[allow(dead_code)]
+pub const QUICKSAVE_SLOTS: usize = 4;
+
+pub struct QuicksaveSlot {
+    pub summary: String, // The active vessel's orbit when saved
+    state: SaveFile,
+}
+
+impl QuicksaveSlot {
+    /// Game seconds simulated when the slot was saved
+    pub fn sim_time(&self) -> f64 {
+        self.state.tick() as f64 * TRAJECTORY_DT
+    }
+}
+
+pub struct Quicksaves {
+    slots: Vec<Option<QuicksaveSlot>>,
+    #[allow(dead_code)]
+    textures: HashMap<String, Texture>, // Decoded textures by source, shared by every slot
+    pub selected: usize,
+    pub show_list: bool,
+}
+
+/// One-line description of the active vessel's orbit around the body pulling on it hardest
+#[allow(dead_code)]
+pub fn orbit_summary(game: &Game) -> String {
+    let player = game.player();
+    if let Some(contact) = player.landed {
+        let state = if contact.crashed { "Crashed" } else { "Landed" };
+        return format!("{} on {}", state, game.planets[contact.planet_index].name);
+    }
+    let Some(body) = game.dominant_body(&player.position) else {
+        return "Drifting".to_string();
+    };
+
+    let planet = &game.planets[body];
+    let mu = game.big_gravity * planet.mass;
+    let r = player.position.subtract(&planet.position);
+    let v = player.velocity.subtract(&planet.velocity);
+    let energy = v.dot(&v) / 2.0 - mu / r.magnitude();
+    if energy >= 0.0 {
+        return format!("Escaping {}", planet.name);
+    }
+
+    let h = r.cross(&v);
+    let semi_major_axis = -mu / (2.0 * energy);
+    let eccentricity = (1.0 + 2.0 * energy * h * h / (mu * mu)).max(0.0).sqrt();
+    let units = &game.units;
+    format!(
+        "{} Pe {} Ap {}",
+        planet.name,
+        units.format_length(semi_major_axis * (1.0 - eccentricity) - planet.radius),
+        units.format_length(semi_major_axis * (1.0 + eccentricity) - planet.radius),
+    )
+}
+
+impl Quicksaves {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self {
+            slots: (0..QUICKSAVE_SLOTS).map(|_| None).collect(),
+            textures: HashMap::new(),
+            selected: 0,
+            show_list: false,
+        }
+    }
+
+    pub fn slots(&self) -> &[Option<QuicksaveSlot>] {
+        &self.slots
+    }
+
+    #[allow(dead_code)]
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % QUICKSAVE_SLOTS;
+    }
+
+    /// Store the game in the selected slot, keeping its textures for later loads
+    #[allow(dead_code)]
+    pub fn save(&mut self, game: &Game, view: ViewState) {
+        for planet in &game.planets {
+            if let (Some(source), Some(texture)) = (&planet.texture_source, &planet.texture) {
+                self.textures.entry(source.clone()).or_insert_with(|| texture.clone());
+            }
+        }
+
+        self.slots[self.selected] = Some(QuicksaveSlot {
+            summary: orbit_summary(game),
+            state: SaveFile::capture(game, view),
+        });
+    }
+
+    /// Rebuild the game in the selected slot, or None if the slot is empty
+    #[allow(dead_code)]
+    pub fn load(&self) -> Option<Result<(Game, ViewState), String>> {
+        let slot = self.slots[self.selected].as_ref()?;
+        Some(slot.state.restore(|source| self.textures.get(source).cloned()))
+    }
+}
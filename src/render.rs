@@ -2,33 +2,121 @@ use crate::game::Game;
 use crate::vector2::Vector2;
 use crate::sprite_renderer::draw_circular_sprite;
 use crate::font::draw_text;
+use crate::docking::{docking_status, DOCKING_CAPTURE_DISTANCE, DOCKING_MAX_ANGLE_ERROR, DOCKING_MAX_CLOSING_SPEED};
+use crate::player::Player;
+use crate::planet::{BodyRole, Planet};
+use crate::lagrange::{lagrange_points, primary_of};
+use crate::flyby::predicted_flybys;
+use crate::quicksave::Quicksaves;
+use crate::ghost::Ghost;
+
+const INACTIVE_VESSEL_COLOR: u32 = 0x00C0FF;
+const LAGRANGE_COLOR: u32 = 0x00FF88;
+const SAIL_COLOR: u32 = 0xDDDDDD;
+const INFO_BUTTON_COLOR: u32 = 0x80C0FF;
+const FLYBY_COLOR: u32 = 0xFFCC66;
+const QUICKSAVE_COLOR: u32 = 0xFFFF66;
+const REPLAY_COLOR: u32 = 0xFF6666;
+const GHOST_COLOR: u32 = 0xB0E0FF;
+const CRASH_COLOR: u32 = 0xFF4040;
+const GHOST_PATH_COLOR: u32 = 0x406080;
+
+// Most points drawn along the ghost's path
+const GHOST_PATH_POINTS: usize = 2000;
+
+// Planet info window placement
+const INFO_X: usize = 50;
+const INFO_Y: usize = 50;
+const INFO_WIDTH: usize = 300;
+const INFO_BUTTON_HEIGHT: usize = 15;
+const INFO_TEXTURE_SIZE: usize = 120; // Size of the displayed texture
+
+/// Reference frame that predicted trajectories are drawn in
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrajectoryFrame {
+    Absolute,
+    Relative,               // Each path relative to the body it orbits
+    Rotating(usize, usize), // Co-rotating with a (primary, secondary) pair
+    Body(usize),            // Every path relative to one body pinned by the user
+}
+
+impl TrajectoryFrame {
+    /// Pin a body as the reference, or release it if it is already pinned
+    #[allow(dead_code)]
+    pub fn toggle_pin(self, body: usize) -> Self {
+        if self == TrajectoryFrame::Body(body) {
+            TrajectoryFrame::Relative
+        } else {
+            TrajectoryFrame::Body(body)
+        }
+    }
+}
+
+/// What a render shows. As in the window, the view is centered on the active
+/// vessel; everything else the user can change is here.
+#[derive(Clone, Copy)]
+pub struct Camera {
+    pub width: usize,
+    pub height: usize,
+    pub zoom_level: f64,
+    pub frame: TrajectoryFrame,
+    pub selected_planet: Option<usize>, // Shows the planet info window
+    pub lagrange_pair: Option<(usize, usize)>,
+    pub time_warp: f64,      // Only shown on the HUD
+    pub thrusting: bool,     // Draws the engine flame
+    pub deterministic: bool, // Shows the tick and state hash
+}
+
+/// What the window draws over the scene while the user is rewinding, saving,
+/// recording or racing; offscreen renders leave all of it out
+#[derive(Clone, Copy, Default)]
+pub struct Overlay<'a> {
+    pub rewinding: bool,
+    pub quicksaves: Option<&'a Quicksaves>, // Listed when the user asks for them
+    pub replay_status: Option<&'a str>,
+    pub ghost: Option<&'a Ghost>,
+}
+
+/// Where the world is drawn: the camera position at the screen center, at a scale
+#[derive(Clone, Copy)]
+struct View {
+    camera_x: f64,
+    camera_y: f64,
+    scale: f64,
+    center_x: usize,
+    center_y: usize,
+}
+
+/// Clickable rows at the bottom of the planet info window
+#[derive(Clone, Copy, PartialEq)]
+pub enum InfoPanelButton {
+    RotatingFrame,
+    PinReference,
+}
+
+pub fn render_game(buffer: &mut [u32], game: &Game, camera: &Camera, overlay: &Overlay) {
+    let Camera { width, height, zoom_level, frame, selected_planet, lagrange_pair, time_warp, thrusting, deterministic } = *camera;
+    let Overlay { rewinding, quicksaves, replay_status, ghost } = *overlay;
 
-pub fn render_game(
-    buffer: &mut [u32],
-    width: usize,
-    height: usize,
-    game: &Game,
-    is_thrusting: bool,
-    zoom_level: f64,
-    time_warp: f64,
-    show_absolute_trajectories: bool,
-    selected_planet: Option<usize>,
-) {
     // Clear to black (space)
     buffer.fill(0x000000);
 
     let center_x = width / 2;
     let center_y = height / 2;
 
-    // Camera follows player
-    let camera_x = game.player.position.x;
-    let camera_y = game.player.position.y;
+    // Camera follows the active vessel
+    let camera_x = game.player().position.x;
+    let camera_y = game.player().position.y;
 
     // Scale: 1 pixel = 1 unit, multiplied by zoom level
     let scale = 1.0 * zoom_level;
+    let view = View { camera_x, camera_y, scale, center_x, center_y };
 
     // Draw orbital predictions
-    draw_orbital_predictions(buffer, width, height, game, camera_x, camera_y, scale, center_x, center_y, show_absolute_trajectories);
+    draw_orbital_predictions(buffer, width, height, game, &view, frame);
+
+    // Annotate gravity assists along the active vessel's path
+    draw_flyby_annotations(buffer, width, height, game, frame, &view);
 
     // Draw planets
     for planet in &game.planets {
@@ -36,6 +124,13 @@ pub fn render_game(
         let screen_y = ((planet.position.y - camera_y) * scale) as i32 + center_y as i32;
         let radius = (planet.radius * scale).max(5.0) as i32;
 
+        // Barycenters are empty points, marked with a small diagonal cross
+        if planet.role == BodyRole::Barycenter {
+            draw_line(buffer, width, height, screen_x - 4, screen_y - 4, screen_x + 4, screen_y + 4, planet.color);
+            draw_line(buffer, width, height, screen_x - 4, screen_y + 4, screen_x + 4, screen_y - 4, planet.color);
+            continue;
+        }
+
         // Draw textured planet if texture available, otherwise solid color
         if let Some(texture) = &planet.texture {
             draw_circular_sprite(buffer, width, height, screen_x, screen_y, radius, texture);
@@ -44,28 +139,47 @@ pub fn render_game(
         }
     }
 
+    // Draw Lagrange point markers for the chosen pair
+    if let Some((primary, secondary)) = lagrange_pair {
+        let points = lagrange_points(game, primary, secondary);
+        for (i, point) in points.iter().enumerate() {
+            let screen_x = ((point.position.x - camera_x) * scale) as i32 + center_x as i32;
+            let screen_y = ((point.position.y - camera_y) * scale) as i32 + center_y as i32;
+            draw_line(buffer, width, height, screen_x - 4, screen_y, screen_x + 4, screen_y, LAGRANGE_COLOR);
+            draw_line(buffer, width, height, screen_x, screen_y - 4, screen_x, screen_y + 4, LAGRANGE_COLOR);
+            if screen_x >= 0 && screen_y >= 0 {
+                draw_text(buffer, width, height, &format!("L{}", i + 1), screen_x as usize + 6, screen_y as usize + 2, LAGRANGE_COLOR);
+            }
+        }
+    }
+
+    // Draw the ghost run and its path
+    if let Some(ghost) = ghost {
+        draw_ghost(buffer, width, height, game, ghost, frame, &view);
+    }
+
+    // Draw inactive vessels where they are
+    for (i, vessel) in game.vessels.iter().enumerate() {
+        if i == game.active_vessel {
+            continue;
+        }
+        let screen_x = ((vessel.position.x - camera_x) * scale) as i32 + center_x as i32;
+        let screen_y = ((vessel.position.y - camera_y) * scale) as i32 + center_y as i32;
+        draw_vessel(buffer, width, height, (screen_x, screen_y), vessel, scale, INACTIVE_VESSEL_COLOR);
+    }
+
     // Draw player as rotated rectangle
-    draw_rotated_triangle(
-        buffer,
-        width,
-        height,
-        center_x as i32,
-        center_y as i32,
-        8,
-        6,
-        game.player.rotation,
-        0xFF0000
-    );
+    draw_vessel(buffer, width, height, (center_x as i32, center_y as i32), game.player(), scale, 0xFF0000);
 
     // Draw thrust flame if thrusting
-    if is_thrusting {
+    if thrusting && game.player().engine_thrust > 0.0 {
         draw_thrust_flame(
             buffer,
             width,
             height,
             center_x as i32,
             center_y as i32,
-            game.player.rotation,
+            game.player().rotation,
             12
         );
     }
@@ -73,39 +187,267 @@ pub fn render_game(
     // Draw time warp indicator in top right
     draw_text(buffer, width, height, &format!("Time Warp: {:.1}x", time_warp), width - 200, 10, 0xFFFFFF);
 
+    // Draw rewind indicator
+    if rewinding {
+        draw_text(buffer, width, height, "<< Rewinding", width / 2 - 36, 10, 0xFFAA00);
+    }
+
+    // Draw recording or replay progress under the rewind indicator
+    if let Some(status) = replay_status {
+        draw_text(buffer, width, height, status, (width / 2).saturating_sub(status.len() * 3), 25, REPLAY_COLOR);
+    }
+
+    // Draw tick and state hash so deterministic runs can be compared
+    if deterministic {
+        draw_text(buffer, width, height, &format!("Tick {} Hash {:016x}", game.tick, game.state_hash()), width - 200, 40, 0x00FFFF);
+    }
+
+    // Draw which vessel is under control when there is more than one
+    if game.vessels.len() > 1 {
+        draw_text(buffer, width, height, &format!("Vessel: {}/{}", game.active_vessel + 1, game.vessels.len()), width - 200, 25, 0xFFFFFF);
+    }
+
     // Draw trajectory mode in top left
-    let mode_text = if show_absolute_trajectories {
-        "Absolute Trajectories"
-    } else {
-        "Planet-Relative Trajectories"
+    let mode_text = match frame {
+        TrajectoryFrame::Absolute => "Absolute Trajectories".to_string(),
+        TrajectoryFrame::Relative => "Planet-Relative Trajectories".to_string(),
+        TrajectoryFrame::Rotating(primary, secondary) => {
+            format!("Rotating Frame: {}-{}", game.planets[primary].name, game.planets[secondary].name)
+        }
+        TrajectoryFrame::Body(reference) => format!("Relative to: {}", game.planets[reference].name),
     };
-    draw_text(buffer, width, height, mode_text, 10, 10, 0xFFFFFF);
+    draw_text(buffer, width, height, &mode_text, 10, 10, 0xFFFFFF);
+
+    // Draw landing status below the trajectory mode
+    if let Some(contact) = game.player().landed {
+        let planet = &game.planets[contact.planet_index];
+        if contact.crashed {
+            draw_text(buffer, width, height, &format!("Crashed on {}", planet.name), 10, 25, CRASH_COLOR);
+        } else {
+            draw_text(buffer, width, height, &format!("Landed on {}", planet.name), 10, 25, 0x00FF00);
+        }
+    }
+
+    // Draw the race against the ghost under the landing status
+    if let Some(ghost) = ghost {
+        draw_text(buffer, width, height, &ghost.status(game), 10, 40, GHOST_COLOR);
+    }
+
+    // Draw docking HUD when another vessel is close
+    draw_docking_hud(buffer, width, height, game);
+
+    // Draw quicksave slots when asked for
+    if let Some(quicksaves) = quicksaves
+        && quicksaves.show_list
+    {
+        draw_quicksave_list(buffer, width, height, quicksaves);
+    }
 
     // Draw planet info window if a planet is selected
-    if let Some(planet_idx) = selected_planet {
-        if planet_idx < game.planets.len() {
-            draw_planet_info(buffer, width, height, &game.planets[planet_idx]);
+    if let Some(planet_idx) = selected_planet
+        && planet_idx < game.planets.len()
+    {
+        draw_planet_info(buffer, width, height, game, planet_idx);
+    }
+}
+
+/// Draw the ghost's whole path, dimmed, and the ghost itself translucent where it is at the current tick
+fn draw_ghost(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    game: &Game,
+    ghost: &Ghost,
+    frame: TrajectoryFrame,
+    view: &View,
+) {
+    let View { camera_x, camera_y, scale, center_x, center_y } = *view;
+    let to_screen = |position: Vector2| {
+        (
+            ((position.x - camera_x) * scale) as i32 + center_x as i32,
+            ((position.y - camera_y) * scale) as i32 + center_y as i32,
+        )
+    };
+
+    let samples = ghost.samples();
+    let stride = samples.len().div_ceil(GHOST_PATH_POINTS).max(1);
+    let mut previous = None;
+    for sample in samples.iter().step_by(stride) {
+        let anchor = match frame {
+            TrajectoryFrame::Absolute => None,
+            TrajectoryFrame::Body(body) => Some(body),
+            _ => sample.reference,
+        };
+        let point = to_screen(sample.position_relative_to(game, anchor));
+        if let Some((x0, y0)) = previous {
+            draw_line(buffer, width, height, x0, y0, point.0, point.1, GHOST_PATH_COLOR);
         }
+        previous = Some(point);
+    }
+
+    if let Some(sample) = ghost.sample_at(game.tick) {
+        let (x, y) = to_screen(sample.position);
+        for_each_triangle_pixel(width, height, x, y, 8, 6, sample.rotation, |index| {
+            buffer[index] = blend(buffer[index], GHOST_COLOR);
+        });
+    }
+}
+
+/// Even mix of two colors, for translucent drawing
+fn blend(under: u32, over: u32) -> u32 {
+    ((under & 0xFEFEFE) >> 1) + ((over & 0xFEFEFE) >> 1)
+}
+
+/// List the quicksave slots under the time warp readout, marking the selected one
+fn draw_quicksave_list(buffer: &mut [u32], width: usize, height: usize, quicksaves: &Quicksaves) {
+    let x = width.saturating_sub(380);
+    let mut y = 60;
+    draw_text(buffer, width, height, "Quicksaves (F5 save, F9 load, F6 slot)", x, y, 0xFFFFFF);
+    y += 15;
+
+    for (i, slot) in quicksaves.slots().iter().enumerate() {
+        let marker = if i == quicksaves.selected { ">" } else { " " };
+        let line = match slot {
+            Some(slot) => format!("{} {}: t {:.1} s  {}", marker, i + 1, slot.sim_time(), slot.summary),
+            None => format!("{} {}: empty", marker, i + 1),
+        };
+        let color = if i == quicksaves.selected { QUICKSAVE_COLOR } else { 0xCCCCCC };
+        draw_text(buffer, width, height, &line, x, y, color);
+        y += 15;
+    }
+}
+
+/// Draw a vessel, plus the vessel docked to it if any, centered on its center of mass
+fn draw_vessel(buffer: &mut [u32], width: usize, height: usize, (cx, cy): (i32, i32), vessel: &Player, scale: f64, color: u32) {
+    if vessel.sail_area_to_mass > 0.0 {
+        draw_sail(buffer, width, height, cx, cy, vessel.rotation);
+    }
+
+    let Some(docked) = vessel.docked else {
+        draw_rotated_triangle(buffer, width, height, cx, cy, 8, 6, vessel.rotation, color);
+        return;
+    };
+
+    let offset = docked.offset.rotate(vessel.rotation).scale(scale);
+    let own_mass = vessel.mass - docked.mass;
+    let core_x = cx - (offset.x * docked.mass / own_mass) as i32;
+    let core_y = cy - (offset.y * docked.mass / own_mass) as i32;
+    draw_rotated_triangle(buffer, width, height, core_x, core_y, 8, 6, vessel.rotation, color);
+    draw_rotated_triangle(buffer, width, height, cx + offset.x as i32, cy + offset.y as i32, 8, 6, vessel.rotation + docked.rotation_offset, color);
+}
+
+/// Draw a sail as a bar across the vessel, perpendicular to the nose
+fn draw_sail(buffer: &mut [u32], width: usize, height: usize, cx: i32, cy: i32, rotation: f64) {
+    let half_span = Vector2 { x: 14.0, y: 0.0 }.rotate(rotation);
+    let x0 = cx - half_span.x as i32;
+    let y0 = cy - half_span.y as i32;
+    let x1 = cx + half_span.x as i32;
+    let y1 = cy + half_span.y as i32;
+    draw_line(buffer, width, height, x0, y0, x1, y1, SAIL_COLOR);
+}
+
+fn draw_docking_hud(buffer: &mut [u32], width: usize, height: usize, game: &Game) {
+    let Some(status) = docking_status(game) else {
+        return;
+    };
+    if height < 80 {
+        return;
     }
+
+    let x = 10;
+    let mut y = height - 70;
+    let ok = |within: bool| if within { 0x00FF00 } else { 0xFFFF00 };
+
+    draw_text(buffer, width, height, &format!("Docking Target: Vessel {}", status.target + 1), x, y, 0xFFFFFF);
+    y += 15;
+
+    let distance = status.relative_position.magnitude();
+    let units = &game.units;
+    draw_text(buffer, width, height, &format!("Rel Pos: ({}, {}) dist {}", units.format_length(status.relative_position.x), units.format_length(status.relative_position.y), units.format_length(distance)), x, y, ok(distance <= DOCKING_CAPTURE_DISTANCE));
+    y += 15;
+
+    let speed = status.relative_velocity.magnitude();
+    draw_text(buffer, width, height, &format!("Rel Vel: ({}, {}) closing {}", units.format_speed(status.relative_velocity.x), units.format_speed(status.relative_velocity.y), units.format_speed(status.closing_speed)), x, y, ok(speed <= DOCKING_MAX_CLOSING_SPEED));
+    y += 15;
+
+    draw_text(buffer, width, height, &format!("Angle Error: {:.1} deg", status.angle_error.to_degrees()), x, y, ok(status.angle_error.abs() <= DOCKING_MAX_ANGLE_ERROR));
 }
 
-fn draw_planet_info(buffer: &mut [u32], width: usize, height: usize, planet: &crate::planet::Planet) {
-    let info_x = 50;
-    let info_y = 50;
-    let info_width = 300;
+/// Actions offered for a planet, labelled for display
+fn info_panel_buttons(game: &Game, planet_idx: usize) -> Vec<(InfoPanelButton, String)> {
+    let mut buttons = vec![(InfoPanelButton::PinReference, "[Pin as reference frame]".to_string())];
+    if let Some(primary) = primary_of(game, planet_idx) {
+        buttons.push((InfoPanelButton::RotatingFrame, format!("[Rotating frame with {}]", game.planets[primary].name)));
+    }
+    buttons
+}
+
+fn description_line_count(planet: &Planet) -> usize {
+    // Assuming ~40 chars per line at 6 pixels per char
+    let chars_per_line = (INFO_WIDTH - 20) / 6;
+    planet.description.len().div_ceil(chars_per_line)
+}
+
+/// Top of the first button row, just below the velocity line
+fn info_panel_buttons_top(planet: &Planet) -> usize {
+    let mut y = INFO_Y + 55;
+    if planet.texture.is_some() {
+        y += INFO_TEXTURE_SIZE + 30;
+    }
+    if !planet.description.is_empty() {
+        y += description_line_count(planet) * 10 + 25;
+    }
+    y + 65
+}
+
+/// The info window button under a screen position, if any
+#[allow(dead_code)]
+pub fn info_panel_button_at(game: &Game, planet_idx: usize, x: f64, y: f64) -> Option<InfoPanelButton> {
+    let top = info_panel_buttons_top(&game.planets[planet_idx]) as f64;
+    if x < INFO_X as f64 || x > (INFO_X + INFO_WIDTH) as f64 || y < top {
+        return None;
+    }
+    let row = ((y - top) / INFO_BUTTON_HEIGHT as f64) as usize;
+    info_panel_buttons(game, planet_idx).get(row).map(|(button, _)| *button)
+}
+
+/// The planet drawn under a screen position, if any
+#[allow(dead_code)]
+pub fn planet_at_screen(game: &Game, zoom_level: f64, width: f64, height: f64, x: f64, y: f64) -> Option<usize> {
+    let center_x = width / 2.0;
+    let center_y = height / 2.0;
+    let camera_x = game.player().position.x;
+    let camera_y = game.player().position.y;
+    let scale = zoom_level;
+
+    game.planets.iter().position(|planet| {
+        let screen_x = ((planet.position.x - camera_x) * scale) + center_x;
+        let screen_y = ((planet.position.y - camera_y) * scale) + center_y;
+        let radius = (planet.radius * scale).max(5.0);
+
+        let dx = x - screen_x;
+        let dy = y - screen_y;
+        dx * dx + dy * dy <= radius * radius
+    })
+}
+
+fn draw_planet_info(buffer: &mut [u32], width: usize, height: usize, game: &Game, planet_idx: usize) {
+    let planet = &game.planets[planet_idx];
+    let units = &game.units;
+    let info_x = INFO_X;
+    let info_y = INFO_Y;
+    let info_width = INFO_WIDTH;
 
     // Calculate height based on content
     let has_texture = planet.texture.is_some();
-    let texture_size = 120; // Size of the displayed texture
+    let texture_size = INFO_TEXTURE_SIZE;
     let has_description = !planet.description.is_empty();
-
-    // Calculate description line count (assuming ~40 chars per line at 6 pixels per char)
-    let chars_per_line = (info_width - 20) / 6;
     let description_lines = if has_description {
-        (planet.description.len() + chars_per_line - 1) / chars_per_line
+        description_line_count(planet)
     } else {
         0
     };
+    let buttons = info_panel_buttons(game, planet_idx);
 
     let mut info_height = 150; // Base height
     if has_texture {
@@ -114,6 +456,7 @@ fn draw_planet_info(buffer: &mut [u32], width: usize, height: usize, planet: &cr
     if has_description {
         info_height += description_lines * 10 + 15;
     }
+    info_height += buttons.len() * INFO_BUTTON_HEIGHT;
 
     // Draw background box
     for y in info_y..info_y + info_height {
@@ -154,8 +497,8 @@ fn draw_planet_info(buffer: &mut [u32], width: usize, height: usize, planet: &cr
     // Draw planet info
     let mut y_offset = info_y + 20;
 
-    // Planet name
-    draw_text(buffer, width, height, &planet.name, info_x + 10, y_offset, 0xFFFFFF);
+    // Planet name and role
+    draw_text(buffer, width, height, &format!("{} ({})", planet.name, planet.role.name()), info_x + 10, y_offset, 0xFFFFFF);
     y_offset += 20;
 
     // Divider
@@ -197,22 +540,29 @@ fn draw_planet_info(buffer: &mut [u32], width: usize, height: usize, planet: &cr
     }
 
     // Mass
-    draw_text(buffer, width, height, &format!("Mass: {:.2e} kg", planet.mass), info_x + 10, y_offset, 0xCCCCCC);
+    draw_text(buffer, width, height, &format!("Mass: {}", units.format_mass(planet.mass)), info_x + 10, y_offset, 0xCCCCCC);
     y_offset += 15;
 
     // Radius
-    draw_text(buffer, width, height, &format!("Radius: {:.0} units", planet.radius), info_x + 10, y_offset, 0xCCCCCC);
+    draw_text(buffer, width, height, &format!("Radius: {}", units.format_length(planet.radius)), info_x + 10, y_offset, 0xCCCCCC);
     y_offset += 15;
 
     // Position
-    draw_text(buffer, width, height, &format!("Position: ({:.0}, {:.0})", planet.position.x, planet.position.y), info_x + 10, y_offset, 0xCCCCCC);
+    draw_text(buffer, width, height, &format!("Position: ({}, {})", units.format_length(planet.position.x), units.format_length(planet.position.y)), info_x + 10, y_offset, 0xCCCCCC);
     y_offset += 15;
 
     // Velocity
     let speed = (planet.velocity.x * planet.velocity.x + planet.velocity.y * planet.velocity.y).sqrt();
-    draw_text(buffer, width, height, &format!("Velocity: {:.2} units/s", speed), info_x + 10, y_offset, 0xCCCCCC);
+    draw_text(buffer, width, height, &format!("Velocity: {}", units.format_speed(speed)), info_x + 10, y_offset, 0xCCCCCC);
+
+    // Action buttons
+    let buttons_top = info_panel_buttons_top(planet);
+    for (row, (_, label)) in buttons.iter().enumerate() {
+        draw_text(buffer, width, height, label, info_x + 10, buttons_top + row * INFO_BUTTON_HEIGHT, INFO_BUTTON_COLOR);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_wrapped_text(buffer: &mut [u32], width: usize, height: usize, text: &str, x: usize, y: usize, max_width: usize, color: u32) {
     let chars_per_line = max_width / 6; // 6 pixels per character
     let mut current_line = 0;
@@ -244,54 +594,201 @@ fn draw_orbital_predictions(
     width: usize,
     height: usize,
     game: &Game,
-    camera_x: f64,
-    camera_y: f64,
-    scale: f64,
-    center_x: usize,
-    center_y: usize,
-    show_absolute: bool,
+    view: &View,
+    frame: TrajectoryFrame,
 ) {
     if !game.cached_trajectories.is_valid {
         return;
     }
 
-    if show_absolute {
-        // Draw in absolute coordinates
-        draw_absolute_trajectories(buffer, width, height, game, camera_x, camera_y, scale, center_x, center_y);
-    } else {
-        // Draw relative to dominant planets
-        draw_relative_trajectories(buffer, width, height, game, camera_x, camera_y, scale, center_x, center_y);
+    match frame {
+        TrajectoryFrame::Absolute => {
+            // Draw in absolute coordinates
+            draw_absolute_trajectories(buffer, width, height, game, view);
+        }
+        TrajectoryFrame::Relative => {
+            // Draw relative to dominant planets
+            draw_relative_trajectories(buffer, width, height, game, view);
+        }
+        TrajectoryFrame::Rotating(primary, secondary) => {
+            // Draw in the frame that turns with the pair, anchored at its present orientation
+            let now = current_pair_axis(game, primary, secondary);
+            let axes: Vec<(Vector2, f64)> = (0..game.cached_trajectories.planet_positions[primary].len())
+                .map(|idx| predicted_pair_axis(game, primary, secondary, idx))
+                .collect();
+            let transform = |idx: usize, position: &Vector2| to_rotating_frame(position, axes[idx], now);
+            draw_transformed_trajectories(buffer, width, height, game, view, transform);
+        }
+        TrajectoryFrame::Body(reference) => {
+            // Follow the pinned body's predicted motion, anchored where it is now
+            let reference_now = game.planets[reference].position;
+            let reference_path = &game.cached_trajectories.planet_positions[reference];
+            let transform = |idx: usize, position: &Vector2| position.subtract(&reference_path[idx]).add(&reference_now);
+            draw_transformed_trajectories(buffer, width, height, game, view, transform);
+        }
     }
 }
 
-fn draw_absolute_trajectories(
+/// Where a point of the active vessel's predicted path is drawn in a frame
+fn vessel_path_point(game: &Game, frame: TrajectoryFrame, idx: usize, position: &Vector2) -> Vector2 {
+    let planet_positions = &game.cached_trajectories.planet_positions;
+    let relative_to = |reference: usize| position.subtract(&planet_positions[reference][idx]).add(&game.planets[reference].position);
+
+    match frame {
+        TrajectoryFrame::Absolute => *position,
+        TrajectoryFrame::Relative => match game.dominant_body(&game.player().position) {
+            Some(reference) => relative_to(reference),
+            None => *position,
+        },
+        TrajectoryFrame::Rotating(primary, secondary) => {
+            to_rotating_frame(position, predicted_pair_axis(game, primary, secondary, idx), current_pair_axis(game, primary, secondary))
+        }
+        TrajectoryFrame::Body(reference) => relative_to(reference),
+    }
+}
+
+/// Mark each predicted flyby at its periapsis with what it does to the orbit
+fn draw_flyby_annotations(
     buffer: &mut [u32],
     width: usize,
     height: usize,
     game: &Game,
-    camera_x: f64,
-    camera_y: f64,
-    scale: f64,
-    center_x: usize,
-    center_y: usize,
+    frame: TrajectoryFrame,
+    view: &View,
+) {
+    let View { camera_x, camera_y, scale, center_x, center_y } = *view;
+    for flyby in predicted_flybys(game, game.active_vessel) {
+        let position = &game.cached_trajectories.vessel_positions[game.active_vessel][flyby.periapsis_index];
+        let point = vessel_path_point(game, frame, flyby.periapsis_index, position);
+        let screen_x = ((point.x - camera_x) * scale) as i32 + center_x as i32;
+        let screen_y = ((point.y - camera_y) * scale) as i32 + center_y as i32;
+
+        draw_circle(buffer, width, height, screen_x, screen_y, 3, FLYBY_COLOR);
+        if screen_x < 0 || screen_y < 0 {
+            continue;
+        }
+
+        let sign = if flyby.delta_v >= 0.0 { "+" } else { "-" };
+        let lines = [
+            format!("{} flyby", game.planets[flyby.body].name),
+            format!("dv {}{}", sign, game.units.format_speed(flyby.delta_v.abs())),
+            format!("Turn {:.1} deg", flyby.turn_angle.to_degrees()),
+            format!("Periapsis {}", game.units.format_length(flyby.periapsis_altitude)),
+        ];
+        for (row, line) in lines.iter().enumerate() {
+            draw_text(buffer, width, height, line, screen_x as usize + 6, screen_y as usize + 2 + row * 10, FLYBY_COLOR);
+        }
+    }
+}
+
+/// Barycenter of a pair and the angle of the line from primary to secondary
+fn pair_axis(primary: &Vector2, primary_mass: f64, secondary: &Vector2, secondary_mass: f64) -> (Vector2, f64) {
+    let barycenter = primary.scale(primary_mass).add(&secondary.scale(secondary_mass)).scale(1.0 / (primary_mass + secondary_mass));
+    let axis = secondary.subtract(primary);
+    (barycenter, axis.y.atan2(axis.x))
+}
+
+/// Barycenter and axis angle of a pair where the bodies are now
+fn current_pair_axis(game: &Game, primary: usize, secondary: usize) -> (Vector2, f64) {
+    let (p, s) = (&game.planets[primary], &game.planets[secondary]);
+    pair_axis(&p.position, p.mass, &s.position, s.mass)
+}
+
+/// Barycenter and axis angle of a pair at a step of the cached prediction
+fn predicted_pair_axis(game: &Game, primary: usize, secondary: usize, idx: usize) -> (Vector2, f64) {
+    let positions = &game.cached_trajectories.planet_positions;
+    pair_axis(&positions[primary][idx], game.planets[primary].mass, &positions[secondary][idx], game.planets[secondary].mass)
+}
+
+/// Carry a predicted point into the frame that turns with a pair, given the
+/// pair's axis at the point's time and now
+fn to_rotating_frame(position: &Vector2, (origin, angle): (Vector2, f64), (origin_now, angle_now): (Vector2, f64)) -> Vector2 {
+    origin_now.add(&position.subtract(&origin).rotate(angle_now - angle))
+}
+
+/// Draw every predicted path after mapping each cached point through a frame transform
+fn draw_transformed_trajectories(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    game: &Game,
+    view: &View,
+    transform: impl Fn(usize, &Vector2) -> Vector2,
 ) {
-    // Draw player trajectory in absolute coordinates
-    let dim_player_color = 0x800000;
-    let mut last_pos: Option<(i32, i32)> = None;
+    let View { camera_x, camera_y, scale, center_x, center_y } = *view;
+    let to_screen = |position: Vector2| {
+        (
+            ((position.x - camera_x) * scale) as i32 + center_x as i32,
+            ((position.y - camera_y) * scale) as i32 + center_y as i32,
+        )
+    };
 
-    for position in &game.cached_trajectories.player_positions {
-        let screen_x = ((position.x - camera_x) * scale) as i32 + center_x as i32;
-        let screen_y = ((position.y - camera_y) * scale) as i32 + center_y as i32;
+    // Vessel trajectories (suspended while landed)
+    for (v, vessel) in game.vessels.iter().enumerate() {
+        if vessel.landed.is_some() {
+            continue;
+        }
+        let dim_vessel_color = vessel_trajectory_color(game, v);
+        let mut last_pos: Option<(i32, i32)> = None;
 
-        if let Some((last_x, last_y)) = last_pos {
-            draw_line(buffer, width, height, last_x, last_y, screen_x, screen_y, dim_player_color);
+        for (idx, position) in game.cached_trajectories.vessel_positions[v].iter().enumerate() {
+            let (screen_x, screen_y) = to_screen(transform(idx, position));
+            if let Some((last_x, last_y)) = last_pos {
+                draw_line(buffer, width, height, last_x, last_y, screen_x, screen_y, dim_vessel_color);
+            }
+            last_pos = Some((screen_x, screen_y));
         }
+    }
+
+    // Planet trajectories
+    for (i, planet) in game.planets.iter().enumerate() {
+        let color = planet.color;
+        let dim_color = ((color >> 16) / 2) << 16 | (((color >> 8) & 0xFF) / 2) << 8 | ((color & 0xFF) / 2);
+        let mut last_pos: Option<(i32, i32)> = None;
 
-        last_pos = Some((screen_x, screen_y));
+        for (idx, position) in game.cached_trajectories.planet_positions[i].iter().enumerate() {
+            let (screen_x, screen_y) = to_screen(transform(idx, position));
+            if let Some((last_x, last_y)) = last_pos {
+                draw_line(buffer, width, height, last_x, last_y, screen_x, screen_y, dim_color);
+            }
+            last_pos = Some((screen_x, screen_y));
+        }
     }
+}
 
-    // Draw planet trajectories in absolute coordinates (skip Sun at index 0)
-    for i in 1..game.planets.len() {
+fn draw_absolute_trajectories(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    game: &Game,
+    view: &View,
+) {
+    let View { camera_x, camera_y, scale, center_x, center_y } = *view;
+    // Draw vessel trajectories in absolute coordinates (suspended while landed)
+    for (v, vessel) in game.vessels.iter().enumerate() {
+        if vessel.landed.is_some() {
+            continue;
+        }
+        let dim_vessel_color = vessel_trajectory_color(game, v);
+        let mut last_pos: Option<(i32, i32)> = None;
+
+        for position in &game.cached_trajectories.vessel_positions[v] {
+            let screen_x = ((position.x - camera_x) * scale) as i32 + center_x as i32;
+            let screen_y = ((position.y - camera_y) * scale) as i32 + center_y as i32;
+
+            if let Some((last_x, last_y)) = last_pos {
+                draw_line(buffer, width, height, last_x, last_y, screen_x, screen_y, dim_vessel_color);
+            }
+
+            last_pos = Some((screen_x, screen_y));
+        }
+    }
+
+    // Draw planet trajectories in absolute coordinates (skip the roots of each system)
+    for i in 0..game.planets.len() {
+        if game.reference_body(i).is_none() {
+            continue;
+        }
         let color = game.planets[i].color;
         let dim_color = ((color >> 16) / 2) << 16 | (((color >> 8) & 0xFF) / 2) << 8 | ((color & 0xFF) / 2);
         let mut last_pos: Option<(i32, i32)> = None;
@@ -314,41 +811,45 @@ fn draw_relative_trajectories(
     width: usize,
     height: usize,
     game: &Game,
-    camera_x: f64,
-    camera_y: f64,
-    scale: f64,
-    center_x: usize,
-    center_y: usize,
+    view: &View,
 ) {
-    // Find dominant planet for player at current position
-    let player_dominant = find_dominant_planet(game, &game.player.position);
+    let View { camera_x, camera_y, scale, center_x, center_y } = *view;
+    // Draw vessel trajectories relative to their dominant planets (suspended while landed)
+    for (v, vessel) in game.vessels.iter().enumerate() {
+        if vessel.landed.is_some() {
+            continue;
+        }
+        let dim_vessel_color = vessel_trajectory_color(game, v);
+        let mut last_pos: Option<(i32, i32)> = None;
+        let Some(vessel_dominant) = game.dominant_body(&vessel.position) else {
+            continue;
+        };
 
-    // Draw player trajectory relative to dominant planet
-    let dim_player_color = 0x800000;
-    let mut last_pos: Option<(i32, i32)> = None;
+        for (idx, position) in game.cached_trajectories.vessel_positions[v].iter().enumerate() {
+            let ref_pos = &game.cached_trajectories.planet_positions[vessel_dominant][idx];
+            let rel_x = position.x - ref_pos.x;
+            let rel_y = position.y - ref_pos.y;
+            let ref_now = &game.planets[vessel_dominant].position;
 
-    for (idx, position) in game.cached_trajectories.player_positions.iter().enumerate() {
-        let ref_pos = &game.cached_trajectories.planet_positions[player_dominant][idx];
-        let rel_x = position.x - ref_pos.x;
-        let rel_y = position.y - ref_pos.y;
-        let ref_now = &game.planets[player_dominant].position;
+            let screen_x = ((rel_x - (camera_x - ref_now.x)) * scale) as i32 + center_x as i32;
+            let screen_y = ((rel_y - (camera_y - ref_now.y)) * scale) as i32 + center_y as i32;
 
-        let screen_x = ((rel_x - (camera_x - ref_now.x)) * scale) as i32 + center_x as i32;
-        let screen_y = ((rel_y - (camera_y - ref_now.y)) * scale) as i32 + center_y as i32;
+            if let Some((last_x, last_y)) = last_pos {
+                draw_line(buffer, width, height, last_x, last_y, screen_x, screen_y, dim_vessel_color);
+            }
 
-        if let Some((last_x, last_y)) = last_pos {
-            draw_line(buffer, width, height, last_x, last_y, screen_x, screen_y, dim_player_color);
+            last_pos = Some((screen_x, screen_y));
         }
-
-        last_pos = Some((screen_x, screen_y));
     }
 
-    // Draw planet trajectories relative to their dominant planets (skip Sun at index 0)
-    for i in 1..game.planets.len() {
+    // Draw planet trajectories relative to the bodies they orbit (skip the roots of each system)
+    for i in 0..game.planets.len() {
+        let Some(planet_dominant) = game.reference_body(i) else {
+            continue;
+        };
         let color = game.planets[i].color;
         let dim_color = ((color >> 16) / 2) << 16 | (((color >> 8) & 0xFF) / 2) << 8 | ((color & 0xFF) / 2);
         let mut last_pos: Option<(i32, i32)> = None;
-        let planet_dominant = find_dominant_planet(game, &game.planets[i].position);
 
         for (idx, position) in game.cached_trajectories.planet_positions[i].iter().enumerate() {
             let ref_pos = &game.cached_trajectories.planet_positions[planet_dominant][idx];
@@ -368,26 +869,15 @@ fn draw_relative_trajectories(
     }
 }
 
-fn find_dominant_planet(game: &Game, position: &Vector2) -> usize {
-    let mut max_accel = 0.0;
-    let mut dominant_idx = 0;
-
-    for (i, planet) in game.planets.iter().enumerate() {
-        let diff = planet.position.subtract(position);
-        let distance = diff.magnitude();
-        if distance > 0.0 {
-            // Gravitational acceleration: a = G * M / r^2
-            let accel = game.big_gravity * planet.mass / (distance * distance);
-            if accel > max_accel {
-                max_accel = accel;
-                dominant_idx = i;
-            }
-        }
+fn vessel_trajectory_color(game: &Game, vessel_idx: usize) -> u32 {
+    if vessel_idx == game.active_vessel {
+        0x800000
+    } else {
+        0x006080
     }
-
-    dominant_idx
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_line(buffer: &mut [u32], width: usize, height: usize, x0: i32, y0: i32, x1: i32, y1: i32, color: u32) {
     // Bresenham's line algorithm
     let dx = (x1 - x0).abs();
@@ -433,39 +923,24 @@ fn draw_circle(buffer: &mut [u32], width: usize, height: usize, cx: i32, cy: i32
     }
 }
 
-fn draw_rotated_rect(
+#[allow(clippy::too_many_arguments)]
+fn draw_rotated_triangle(
     buffer: &mut [u32],
     width: usize,
     height: usize,
     cx: i32,
     cy: i32,
-    rect_width: i32,
-    rect_height: i32,
+    length: i32,     // distance from center to tip
+    half_base: i32,  // half the width of the base
     rotation: f64,
-    color: u32
+    color: u32,
 ) {
-    let cos_r = rotation.cos();
-    let sin_r = rotation.sin();
-
-    // Draw filled rotated rectangle
-    for local_y in -rect_height..=rect_height {
-        for local_x in -rect_width..=rect_width {
-            // Rotate the point
-            let rotated_x = (local_x as f64 * cos_r - local_y as f64 * sin_r) as i32;
-            let rotated_y = (local_x as f64 * sin_r + local_y as f64 * cos_r) as i32;
-
-            let px = cx + rotated_x;
-            let py = cy + rotated_y;
-
-            if px >= 0 && px < width as i32 && py >= 0 && py < height as i32 {
-                buffer[py as usize * width + px as usize] = color;
-            }
-        }
-    }
+    for_each_triangle_pixel(width, height, cx, cy, length, half_base, rotation, |index| buffer[index] = color);
 }
 
-fn draw_rotated_triangle(
-    buffer: &mut [u32],
+/// Visit the buffer index of every on-screen pixel of a rotated triangle
+#[allow(clippy::too_many_arguments)]
+fn for_each_triangle_pixel(
     width: usize,
     height: usize,
     cx: i32,
@@ -473,9 +948,9 @@ fn draw_rotated_triangle(
     length: i32,     // distance from center to tip
     half_base: i32,  // half the width of the base
     rotation: f64,
-    color: u32,
+    mut plot: impl FnMut(usize),
 ) {
-    let adj_rot:f64 = rotation + 1.5707;
+    let adj_rot: f64 = rotation + std::f64::consts::FRAC_PI_2;
     let cos_r = adj_rot.cos();
     let sin_r = adj_rot.sin();
 
@@ -504,7 +979,7 @@ fn draw_rotated_triangle(
                 let py = cy + ry;
 
                 if px >= 0 && px < width as i32 && py >= 0 && py < height as i32 {
-                    buffer[py as usize * width + px as usize] = color;
+                    plot(py as usize * width + px as usize);
                 }
             }
         }
@@ -0,0 +1,322 @@
+//! Input recordings: the starting state plus every frame's controls, played
+//! back through the same frame loop so a flight reproduces exactly
+
+use crate::game::Game;
+use crate::keyboard_input::InputState;
+use crate::lagrange::place_vessel_at;
+use crate::save::{resolve_texture, SaveFile, ViewState};
+use crate::texture::Texture;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Bump whenever the layout of RecordingFile changes
+#[allow(dead_code)]
+pub const RECORDING_VERSION: u32 = 1;
+
+// Where the native build writes its recording
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(dead_code)]
+pub const DEFAULT_RECORDING_PATH: &str = "spaceflight_elephant.rec";
+
+// Recording time skipped by one seek
+#[allow(dead_code)]
+pub const SEEK_SECONDS: f64 = 5.0;
+
+// Most recorded frames played per displayed frame
+#[allow(dead_code)]
+const MAX_PLAYBACK_SPEED: usize = 16;
+
+// Bits of FrameInput::controls
+#[allow(dead_code)]
+const ROTATE_LEFT: u8 = 1;
+#[allow(dead_code)]
+const ROTATE_RIGHT: u8 = 2;
+#[allow(dead_code)]
+const THRUST: u8 = 4;
+#[allow(dead_code)]
+const REWIND: u8 = 8;
+#[allow(dead_code)]
+const DETERMINISTIC: u8 = 16;
+
+/// Everything the frame loop consumed in one frame
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub struct FrameInput {
+    pub dt: f64,
+    pub time_warp: f64,
+    controls: u8,
+}
+
+#[allow(dead_code)]
+impl FrameInput {
+    pub fn capture(input_state: &InputState, dt: f64, time_warp: f64, deterministic: bool) -> Self {
+        let bit = |on: bool, flag: u8| if on { flag } else { 0 };
+        Self {
+            dt,
+            time_warp,
+            controls: bit(input_state.rotate_left, ROTATE_LEFT)
+                | bit(input_state.rotate_right, ROTATE_RIGHT)
+                | bit(input_state.thrust, THRUST)
+                | bit(input_state.rewind, REWIND)
+                | bit(deterministic, DETERMINISTIC),
+        }
+    }
+
+    fn input_state(&self) -> InputState {
+        let mut input_state = InputState::new();
+        input_state.rotate_left = self.controls & ROTATE_LEFT != 0;
+        input_state.rotate_right = self.controls & ROTATE_RIGHT != 0;
+        input_state.thrust = self.controls & THRUST != 0;
+        input_state.rewind = self.controls & REWIND != 0;
+        input_state
+    }
+
+    fn deterministic(&self) -> bool {
+        self.controls & DETERMINISTIC != 0
+    }
+}
+
+/// A command that changes the simulation outside the flight controls
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub enum ReplayAction {
+    CycleVessel,
+    Undock,
+    PlaceAtLagrange { primary: usize, secondary: usize, point: usize },
+}
+
+impl ReplayAction {
+    #[allow(dead_code)]
+    pub fn apply(&self, game: &mut Game) {
+        match *self {
+            ReplayAction::CycleVessel => game.cycle_active_vessel(),
+            ReplayAction::Undock => game.undock(),
+            ReplayAction::PlaceAtLagrange { primary, secondary, point } => place_vessel_at(game, primary, secondary, point),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[allow(dead_code)]
+struct RecordingFile {
+    version: u32,
+    start_accumulator: f64,
+    start: SaveFile,
+    frames: Vec<(f64, f64, u8)>,          // (dt, time warp, control bits), one line per frame
+    actions: Vec<(usize, ReplayAction)>, // Applied just before the frame with this index
+}
+
+#[derive(Clone)]
+#[allow(dead_code)]
+pub struct Recording {
+    start: SaveFile,
+    start_accumulator: f64,
+    frames: Vec<FrameInput>,
+    actions: Vec<(usize, ReplayAction)>,
+}
+
+#[allow(dead_code)]
+impl Recording {
+    /// Begin recording from the game as it is now. Rewind history is cleared so
+    /// that rewinding during the recording cannot reach back past its start.
+    pub fn start(game: &mut Game, view: ViewState, time_accumulator: f64) -> Self {
+        game.history.clear();
+        Self {
+            start: SaveFile::capture(game, view),
+            start_accumulator: time_accumulator,
+            frames: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn push_frame(&mut self, frame: FrameInput) {
+        self.frames.push(frame);
+    }
+
+    pub fn push_action(&mut self, action: ReplayAction) {
+        self.actions.push((self.frames.len(), action));
+    }
+
+    /// Real seconds covered by the recorded frames
+    pub fn duration(&self) -> f64 {
+        self.frames.iter().map(|frame| frame.dt).sum()
+    }
+
+    pub fn to_text(&self) -> Result<String, String> {
+        let file = RecordingFile {
+            version: RECORDING_VERSION,
+            start_accumulator: self.start_accumulator,
+            start: self.start.clone(),
+            frames: self.frames.iter().map(|frame| (frame.dt, frame.time_warp, frame.controls)).collect(),
+            actions: self.actions.clone(),
+        };
+        toml::to_string_pretty(&file).map_err(|e| format!("Failed to save recording: {}", e))
+    }
+
+    pub fn from_text(source: &str) -> Result<Self, String> {
+        let file: RecordingFile = toml::from_str(source).map_err(|e| format!("Invalid recording: {}", e))?;
+        if file.version != RECORDING_VERSION {
+            return Err(format!("Recording version {} is not supported (expected {})", file.version, RECORDING_VERSION));
+        }
+        Ok(Self {
+            start: file.start,
+            start_accumulator: file.start_accumulator,
+            frames: file.frames.into_iter().map(|(dt, time_warp, controls)| FrameInput { dt, time_warp, controls }).collect(),
+            actions: file.actions,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_file(&self, path: &str) -> Result<(), String> {
+        let text = self.to_text()?;
+        std::fs::write(path, text).map_err(|e| format!("Failed to write recording {}: {}", path, e))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_file(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read recording {}: {}", path, e))?;
+        Self::from_text(&source).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+/// A recording being played back onto a game, with its own clock and controls
+#[allow(dead_code)]
+pub struct Playback {
+    recording: Recording,
+    textures: HashMap<String, Texture>, // Decoded once, reused when seeking back restarts
+    input_state: InputState,
+    time_accumulator: f64,
+    frame: usize,  // Next frame to play
+    action: usize, // Next action to apply
+    elapsed: f64,
+    pub paused: bool,
+    pub speed: usize, // Recorded frames played per displayed frame
+}
+
+#[allow(dead_code)]
+impl Playback {
+    /// Restore the recording's starting state, ready to play
+    pub fn start(recording: Recording) -> Result<(Self, Game, ViewState), String> {
+        let mut textures = HashMap::new();
+        for source in recording.start.texture_sources() {
+            if let Some(texture) = resolve_texture(source) {
+                textures.insert(source.to_string(), texture);
+            }
+        }
+
+        let (game, view) = recording.start.restore(|source| textures.get(source).cloned())?;
+        let playback = Self {
+            time_accumulator: recording.start_accumulator,
+            recording,
+            textures,
+            input_state: InputState::new(),
+            frame: 0,
+            action: 0,
+            elapsed: 0.0,
+            paused: false,
+            speed: 1,
+        };
+        Ok((playback, game, view))
+    }
+
+    /// Controls of the frame played last, for drawing the thrust flame
+    pub fn input_state(&self) -> &InputState {
+        &self.input_state
+    }
+
+    /// Time warp of the frame played last
+    pub fn time_warp(&self) -> f64 {
+        self.frame.checked_sub(1).map_or(1.0, |i| self.recording.frames[i].time_warp)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame == self.recording.frames.len()
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2).min(MAX_PLAYBACK_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2).max(1);
+    }
+
+    /// Hand the recording back, e.g. to play it again later
+    pub fn into_recording(self) -> Recording {
+        self.recording
+    }
+
+    fn play_frame(&mut self, game: &mut Game) {
+        let frame = self.recording.frames[self.frame];
+        while let Some((index, action)) = self.recording.actions.get(self.action)
+            && *index == self.frame
+        {
+            action.apply(game);
+            self.action += 1;
+        }
+
+        self.input_state = frame.input_state();
+        self.input_state.advance_frame(game, &mut self.time_accumulator, frame.dt, frame.time_warp, frame.deterministic());
+        self.elapsed += frame.dt;
+        self.frame += 1;
+    }
+
+    /// Play the next frame whatever the pause and speed, returning false once the recording has ended
+    pub fn step(&mut self, game: &mut Game) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        self.play_frame(game);
+        true
+    }
+
+    /// Play this display frame's share of the recording
+    pub fn update(&mut self, game: &mut Game) {
+        if self.paused {
+            return;
+        }
+        for _ in 0..self.speed {
+            if !self.step(game) {
+                break;
+            }
+        }
+    }
+
+    /// A fresh copy of the game as the recording starts
+    pub fn start_state(&self) -> Result<(Game, ViewState), String> {
+        self.recording.start.restore(|source| self.textures.get(source).cloned())
+    }
+
+    /// Jump by `seconds` of recording time. Seeking back replays from the
+    /// start, since the simulation only runs forward.
+    pub fn seek(&mut self, game: &mut Game, seconds: f64) {
+        let target = (self.elapsed + seconds).max(0.0);
+        if target < self.elapsed {
+            let Ok((restarted, _)) = self.start_state() else {
+                return;
+            };
+            *game = restarted;
+            self.input_state = InputState::new();
+            self.time_accumulator = self.recording.start_accumulator;
+            self.frame = 0;
+            self.action = 0;
+            self.elapsed = 0.0;
+        }
+
+        while !self.is_finished() && self.elapsed + self.recording.frames[self.frame].dt <= target {
+            self.play_frame(game);
+        }
+    }
+
+    /// HUD line describing where playback is
+    pub fn status(&self) -> String {
+        let state = if self.is_finished() {
+            " (ended)"
+        } else if self.paused {
+            " (paused)"
+        } else {
+            ""
+        };
+        format!("Replay {:.1} / {:.1} s  {}x{}", self.elapsed, self.recording.duration(), self.speed, state)
+    }
+}
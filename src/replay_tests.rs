@@ -0,0 +1,93 @@
+//! A recorded flight plays back to exactly the states the live run went through.
+
+use crate::game::{Game, DEFAULT_BIG_GRAVITY};
+use crate::initial_universe::calculate_stable_orbit;
+use crate::keyboard_input::InputState;
+use crate::planet::{BodyRole, Planet};
+use crate::player::Player;
+use crate::replay::{FrameInput, Playback, Recording, ReplayAction};
+use crate::save::ViewState;
+use crate::vector2::Vector2;
+
+// A power of two, so frame times add up exactly and seeks land on whole frames
+const FRAME_DT: f64 = 1.0 / 64.0;
+const FRAMES: usize = 120;
+
+// Look-ahead for the test's games; the states a game goes through don't depend on it
+const PREDICTION_STEPS: usize = 1000;
+
+/// A star and a planet, with two vessels in orbit around the planet
+fn system() -> Game {
+    let origin = Vector2 { x: 0.0, y: 0.0 };
+    let star = Planet::new("Star".to_string(), 300.0, 1e15, origin, origin, 0xFFFF00).with_role(BodyRole::Star);
+    let (position, velocity) = calculate_stable_orbit(origin, origin, star.mass, 15000.0, 0.0, DEFAULT_BIG_GRAVITY);
+    let planet = Planet::new("Planet".to_string(), 150.0, 6e12, position, velocity, 0x4040FF);
+    let vessels = [400.0, 600.0].map(|altitude| {
+        let (position, velocity) = calculate_stable_orbit(planet.position, planet.velocity, planet.mass, planet.radius + altitude, 0.0, DEFAULT_BIG_GRAVITY);
+        Player::new(position, velocity, 1.0, 0.0)
+    });
+    let mut game = Game::new(vec![star, planet], vessels.to_vec());
+    game.prediction_steps = PREDICTION_STEPS;
+    game
+}
+
+/// What the pilot does in frame `i`: controls, time warp, fixed-tick mode and any action taken
+fn script(i: usize) -> (InputState, f64, bool, Option<ReplayAction>) {
+    let mut input = InputState::new();
+    input.thrust = (10..16).contains(&i) || (70..74).contains(&i);
+    input.rotate_left = (20..30).contains(&i);
+    input.rotate_right = (60..66).contains(&i);
+    input.rewind = (45..50).contains(&i);
+    let time_warp = if (30..60).contains(&i) { 4.0 } else { 1.0 };
+    let action = match i {
+        25 => Some(ReplayAction::CycleVessel),
+        80 => Some(ReplayAction::PlaceAtLagrange { primary: 0, secondary: 1, point: 3 }),
+        _ => None,
+    };
+    (input, time_warp, i >= 90, action)
+}
+
+/// Fly the script live while recording it, returning the recording and the state hash after every frame
+fn fly() -> (Recording, Vec<u64>) {
+    let mut game = system();
+    let mut time_accumulator = 0.0;
+    let view = ViewState { zoom_level: 1.0, time_warp: 1.0, selected_planet: None };
+    let mut recording = Recording::start(&mut game, view, time_accumulator);
+
+    let mut hashes = Vec::new();
+    for i in 0..FRAMES {
+        let (input, time_warp, deterministic, action) = script(i);
+        if let Some(action) = action {
+            recording.push_action(action);
+            action.apply(&mut game);
+        }
+        recording.push_frame(FrameInput::capture(&input, FRAME_DT, time_warp, deterministic));
+        input.advance_frame(&mut game, &mut time_accumulator, FRAME_DT, time_warp, deterministic);
+        hashes.push(game.state_hash());
+    }
+    (recording, hashes)
+}
+
+#[test]
+fn playback_matches_the_live_run() {
+    let (recording, hashes) = fly();
+    let recording = Recording::from_text(&recording.to_text().unwrap()).expect("recording reads back");
+    let (mut playback, mut game, _) = Playback::start(recording).expect("recording starts");
+    game.prediction_steps = PREDICTION_STEPS;
+
+    for (i, hash) in hashes.iter().enumerate() {
+        assert!(playback.step(&mut game));
+        assert_eq!(game.state_hash(), *hash, "frame {}", i);
+    }
+    assert!(!playback.step(&mut game));
+
+    // Seeking back replays from the start to the frame that ends on the target time
+    let seconds = 1.5;
+    playback.seek(&mut game, -seconds);
+    let frame = (FRAMES as f64 * FRAME_DT - seconds) / FRAME_DT;
+    assert_eq!(game.state_hash(), hashes[frame as usize - 1]);
+
+    game.prediction_steps = PREDICTION_STEPS;
+    while playback.step(&mut game) {}
+    assert_eq!(game.state_hash(), hashes[FRAMES - 1]);
+}
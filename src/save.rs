@@ -0,0 +1,140 @@
+//! Saved games: the full simulation state plus the view, as versioned TOML
+
+use crate::game::{Game, TRAJECTORY_DT};
+use crate::generator::generated_texture;
+use crate::initial_universe::embedded_texture;
+use crate::planet::Planet;
+use crate::player::Player;
+use crate::texture::Texture;
+use crate::units::UnitSystem;
+use serde::{Deserialize, Serialize};
+
+// Bump whenever the layout of SaveFile changes
+pub const SAVE_VERSION: u32 = 1;
+
+// Where the native build writes its save
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(dead_code)]
+pub const DEFAULT_SAVE_PATH: &str = "spaceflight_elephant.sav";
+
+/// Camera and clock settings restored along with the simulation
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ViewState {
+    pub zoom_level: f64,
+    pub time_warp: f64,
+    pub selected_planet: Option<usize>,
+}
+
+/// Read first on its own, so files from other versions fail with a clear message
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// Everything a save holds; kept in memory for quicksaves or written out as TOML
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveFile {
+    version: u32,
+    tick: u64,
+    elapsed_time: f64, // Game seconds, for readers; tick is what gets restored
+    big_gravity: f64,
+    units: UnitSystem,
+    active_vessel: usize,
+    view: ViewState,
+    planets: Vec<Planet>, // Textures are stored by texture_source
+    vessels: Vec<Player>,
+}
+
+/// Rebuild a texture from its recorded source: a generated texture, an
+/// embedded resource, or a file on disk
+pub fn resolve_texture(source: &str) -> Option<Texture> {
+    if source.starts_with("generated:") {
+        return generated_texture(source);
+    }
+    match embedded_texture(source) {
+        Ok(texture) => texture,
+        Err(_) => Texture::load_from_file(source).ok(),
+    }
+}
+
+impl SaveFile {
+    /// Copy the game and view. Textures are left behind and rewind history is not saved.
+    #[allow(dead_code)]
+    pub fn capture(game: &Game, view: ViewState) -> Self {
+        let mut planets = game.planets.clone();
+        for planet in &mut planets {
+            planet.texture = None;
+        }
+
+        Self {
+            version: SAVE_VERSION,
+            tick: game.tick,
+            elapsed_time: game.tick as f64 * TRAJECTORY_DT,
+            big_gravity: game.big_gravity,
+            units: game.units,
+            active_vessel: game.active_vessel,
+            view,
+            planets,
+            vessels: game.vessels.clone(),
+        }
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    #[allow(dead_code)]
+    pub fn texture_sources(&self) -> impl Iterator<Item = &str> {
+        self.planets.iter().filter_map(|planet| planet.texture_source.as_deref())
+    }
+
+    /// Rebuild the game, fetching textures by source; predictions are made on its first frame
+    pub fn restore(&self, texture: impl Fn(&str) -> Option<Texture>) -> Result<(Game, ViewState), String> {
+        if self.vessels.is_empty() || self.active_vessel >= self.vessels.len() {
+            return Err("Save file has no active vessel".to_string());
+        }
+        let mut view = self.view;
+        view.selected_planet = view.selected_planet.filter(|&i| i < self.planets.len());
+
+        let mut planets = self.planets.clone();
+        for planet in &mut planets {
+            planet.texture = planet.texture_source.as_deref().and_then(&texture);
+        }
+
+        let mut game = Game::with_gravity(planets, self.vessels.clone(), self.big_gravity, self.units);
+        game.tick = self.tick;
+        game.active_vessel = self.active_vessel;
+        Ok((game, view))
+    }
+}
+
+/// Serialize the game and view
+#[allow(dead_code)]
+pub fn save_game(game: &Game, view: ViewState) -> Result<String, String> {
+    toml::to_string(&SaveFile::capture(game, view)).map_err(|e| format!("Failed to save game: {}", e))
+}
+
+/// Restore a game saved by `save_game`
+pub fn load_game(source: &str) -> Result<(Game, ViewState), String> {
+    let header: SaveHeader = toml::from_str(source).map_err(|e| format!("Invalid save file: {}", e))?;
+    if header.version != SAVE_VERSION {
+        return Err(format!("Save file version {} is not supported (expected {})", header.version, SAVE_VERSION));
+    }
+    let file: SaveFile = toml::from_str(source).map_err(|e| format!("Invalid save file: {}", e))?;
+    file.restore(resolve_texture)
+}
+
+/// Write a save file to disk
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(dead_code)]
+pub fn save_game_file(path: &str, game: &Game, view: ViewState) -> Result<(), String> {
+    let text = save_game(game, view)?;
+    std::fs::write(path, text).map_err(|e| format!("Failed to write save {}: {}", path, e))
+}
+
+/// Read a save file from disk
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_game_file(path: &str) -> Result<(Game, ViewState), String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read save {}: {}", path, e))?;
+    load_game(&source).map_err(|e| format!("{}: {}", path, e))
+}
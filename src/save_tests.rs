@@ -0,0 +1,53 @@
+//! Saving and loading a game keeps the simulation exactly as it was.
+
+use crate::game::Game;
+use crate::initial_universe::scenario_by_name;
+use crate::save::{load_game, save_game, ViewState, SAVE_VERSION};
+
+fn view() -> ViewState {
+    ViewState { zoom_level: 0.25, time_warp: 4.0, selected_planet: Some(2) }
+}
+
+/// A built-in scenario a few hundred ticks in, with a vessel switched to
+fn played(name: &str) -> Game {
+    let mut game = scenario_by_name(name).expect("built-in scenario exists");
+    game.prediction_steps = 0;
+    game.cycle_active_vessel();
+    for _ in 0..300 {
+        game.advance_trajectory();
+    }
+    game
+}
+
+#[test]
+fn save_round_trip() {
+    for name in ["default", "solar-system"] {
+        let mut game = played(name);
+        let (mut loaded, view) = load_game(&save_game(&game, view()).unwrap()).expect("save loads");
+
+        assert_eq!(loaded.state_hash(), game.state_hash(), "{}", name);
+        assert_eq!(loaded.tick, game.tick);
+        assert_eq!(loaded.active_vessel, game.active_vessel);
+        assert_eq!((view.zoom_level, view.time_warp, view.selected_planet), (0.25, 4.0, Some(2)));
+
+        // Saves keep textures by source, so every texture needs one to come back
+        assert!(game.planets.iter().all(|planet| planet.texture.is_none() || planet.texture_source.is_some()), "{}", name);
+        let textures = |game: &Game| game.planets.iter().map(|planet| (planet.texture.is_some(), planet.texture_source.clone())).collect::<Vec<_>>();
+        assert_eq!(textures(&loaded), textures(&game), "{}", name);
+
+        // The loaded game carries on exactly as the original
+        loaded.prediction_steps = 0;
+        for _ in 0..300 {
+            game.advance_trajectory();
+            loaded.advance_trajectory();
+        }
+        assert_eq!(loaded.state_hash(), game.state_hash(), "{}", name);
+    }
+}
+
+#[test]
+fn other_save_versions_are_refused() {
+    let text = save_game(&played("default"), view()).unwrap();
+    let text = text.replacen(&format!("version = {}", SAVE_VERSION), &format!("version = {}", SAVE_VERSION + 1), 1);
+    assert!(load_game(&text).is_err());
+}
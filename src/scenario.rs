@@ -0,0 +1,258 @@
+//! Scenario files: global settings plus bodies and vessels, loaded from TOML
+
+use crate::ephemeris::{length_unit_m, time_unit_s, EphemerisTable, ImportSettings, ImportedStates, ReferencePlane};
+use crate::game::Game;
+use crate::initial_universe::calculate_orbit;
+use crate::planet::{BodyRole, Planet};
+use crate::player::Player;
+use crate::texture::Texture;
+use crate::units::UnitSystem;
+use crate::vector2::Vector2;
+use serde::Deserialize;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    big_gravity: Option<f64>,
+    units: Option<UnitsSpec>,
+    ephemeris: Option<EphemerisSpec>,
+    #[serde(default)]
+    bodies: Vec<BodySpec>,
+    #[serde(default)]
+    vessels: Vec<VesselSpec>,
+}
+
+/// SI size of one game unit of length, mass and time
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnitsSpec {
+    length_m: f64,
+    mass_kg: f64,
+    time_s: f64,
+}
+
+/// External state tables that bodies and vessels can start from
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EphemerisSpec {
+    files: Vec<String>,
+    epoch: Option<f64>,          // In the tables' time column: Julian date for Horizons
+    length_unit: Option<String>, // m, km or au; overrides what the tables state
+    time_unit: Option<String>,   // s or day
+    plane: Option<String>,       // ecliptic or equatorial
+    center: Option<String>,      // Table body moved to rest at the origin
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BodySpec {
+    name: String,
+    role: Option<String>,
+    radius: f64,
+    mass: f64,
+    position: Option<[f64; 2]>,
+    velocity: Option<[f64; 2]>,
+    orbit: Option<OrbitSpec>,
+    ephemeris: Option<String>, // Name in the ephemeris tables
+    color: u32,
+    texture: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    luminosity: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VesselSpec {
+    mass: f64,
+    #[serde(default)]
+    rotation: f64,
+    position: Option<[f64; 2]>,
+    velocity: Option<[f64; 2]>,
+    orbit: Option<OrbitSpec>,
+    ephemeris: Option<String>,
+    sail_area_to_mass: Option<f64>,
+    engine_thrust: Option<f64>,
+}
+
+/// Orbital elements around a named parent, starting at periapsis
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OrbitSpec {
+    parent: String,
+    periapsis: Option<f64>,
+    semi_major_axis: Option<f64>,
+    #[serde(default)]
+    eccentricity: f64,
+    #[serde(default)]
+    argument_of_periapsis: f64, // Degrees
+}
+
+fn vector(value: [f64; 2]) -> Vector2 {
+    Vector2 { x: value[0], y: value[1] }
+}
+
+fn parse_role(role: &str) -> Result<BodyRole, String> {
+    match role {
+        "star" => Ok(BodyRole::Star),
+        "planet" => Ok(BodyRole::Planet),
+        "moon" => Ok(BodyRole::Moon),
+        "barycenter" => Ok(BodyRole::Barycenter),
+        _ => Err(format!("unknown role '{}' (expected star, planet, moon or barycenter)", role)),
+    }
+}
+
+/// Read the ephemeris tables and take every body's state at the epoch
+fn import_ephemeris(spec: &EphemerisSpec, units: &UnitSystem, read_table: impl Fn(&str) -> Result<String, String>) -> Result<ImportedStates, String> {
+    let tables = spec
+        .files
+        .iter()
+        .map(|file| read_table(file).and_then(|source| EphemerisTable::parse(&source)).map_err(|e| format!("Ephemeris {}: {}", file, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let settings = ImportSettings {
+        epoch: spec.epoch,
+        length_m: spec.length_unit.as_deref().map(length_unit_m).transpose()?,
+        time_s: spec.time_unit.as_deref().map(time_unit_s).transpose()?,
+        plane: spec.plane.as_deref().map(ReferencePlane::parse).transpose()?,
+        center: spec.center.clone(),
+    };
+    ImportedStates::from_tables(&tables, &settings, units)
+}
+
+/// State of something placed from the ephemeris tables, if it names an entry in them
+fn ephemeris_state(what: &str, name: Option<&str>, imported: &ImportedStates) -> Result<Option<(Vector2, Vector2)>, String> {
+    name.map(|name| imported.get(name).ok_or_else(|| format!("{} is not in the ephemeris tables as '{}'", what, name))).transpose()
+}
+
+/// Position and velocity of something placed by state vector, by orbit or from the ephemeris tables
+fn resolve_state(
+    what: &str,
+    position: Option<[f64; 2]>,
+    velocity: Option<[f64; 2]>,
+    orbit: Option<&OrbitSpec>,
+    ephemeris: Option<(Vector2, Vector2)>,
+    planets: &[Planet],
+    big_gravity: f64,
+) -> Result<(Vector2, Vector2), String> {
+    if let Some(state) = ephemeris {
+        if orbit.is_some() || position.is_some() {
+            return Err(format!("{} has an ephemeris entry as well as an orbit or position", what));
+        }
+        return Ok(state);
+    }
+
+    match (orbit, position) {
+        (Some(_), Some(_)) => Err(format!("{} has both an orbit and a position", what)),
+        (None, Some(position)) => Ok((vector(position), vector(velocity.unwrap_or([0.0, 0.0])))),
+        (None, None) => Err(format!("{} needs an orbit, a position or an ephemeris entry", what)),
+        (Some(orbit), None) => {
+            let parent = planets
+                .iter()
+                .find(|planet| planet.name == orbit.parent)
+                .ok_or_else(|| format!("{} orbits unknown parent '{}' (parents must be listed before what orbits them)", what, orbit.parent))?;
+
+            if !(0.0..1.0).contains(&orbit.eccentricity) {
+                return Err(format!("{} has eccentricity {}, which is not a closed orbit", what, orbit.eccentricity));
+            }
+            let periapsis = match (orbit.periapsis, orbit.semi_major_axis) {
+                (Some(periapsis), None) => periapsis,
+                (None, Some(semi_major_axis)) => semi_major_axis * (1.0 - orbit.eccentricity),
+                _ => return Err(format!("{} orbit needs exactly one of periapsis or semi_major_axis", what)),
+            };
+
+            Ok(calculate_orbit(
+                parent.position,
+                parent.velocity,
+                parent.mass,
+                periapsis,
+                orbit.eccentricity,
+                orbit.argument_of_periapsis.to_radians(),
+                big_gravity,
+            ))
+        }
+    }
+}
+
+/// Build a game from scenario TOML; textures are fetched through `load_texture`,
+/// which may return None to leave a body with its fallback color, and
+/// ephemeris tables are read through `read_table`
+pub fn load_scenario(
+    source: &str,
+    load_texture: impl Fn(&str) -> Result<Option<Texture>, String>,
+    read_table: impl Fn(&str) -> Result<String, String>,
+) -> Result<Game, String> {
+    let file: ScenarioFile = toml::from_str(source).map_err(|e| format!("Invalid scenario file: {}", e))?;
+
+    let units = file
+        .units
+        .map(|units| UnitSystem::new(units.length_m, units.mass_kg, units.time_s))
+        .unwrap_or_else(UnitSystem::game_default);
+    let big_gravity = file.big_gravity.unwrap_or_else(|| units.gravitational_constant());
+    let imported = match &file.ephemeris {
+        Some(spec) => import_ephemeris(spec, &units, read_table)?,
+        None => ImportedStates::new(),
+    };
+
+    let mut planets: Vec<Planet> = Vec::with_capacity(file.bodies.len());
+    for body in &file.bodies {
+        let what = format!("Body '{}'", body.name);
+        if planets.iter().any(|planet| planet.name == body.name) {
+            return Err(format!("{} is defined twice", what));
+        }
+
+        let (position, velocity) = resolve_state(&what, body.position, body.velocity, body.orbit.as_ref(), ephemeris_state(&what, body.ephemeris.as_deref(), &imported)?, &planets, big_gravity)?;
+        let mut planet = Planet::new(body.name.clone(), body.radius, body.mass, position, velocity, body.color)
+            .with_description(body.description.clone())
+            .with_luminosity(body.luminosity);
+        if let Some(role) = &body.role {
+            planet = planet.with_role(parse_role(role).map_err(|e| format!("{} has an {}", what, e))?);
+        }
+        if let Some(path) = &body.texture {
+            planet = planet.with_texture_source(path.clone());
+            if let Some(texture) = load_texture(path).map_err(|e| format!("{} has a bad texture: {}", what, e))? {
+                planet = planet.with_texture(texture);
+            }
+        }
+        planets.push(planet);
+    }
+
+    if file.vessels.is_empty() {
+        return Err("Scenario has no vessels".to_string());
+    }
+    let mut vessels = Vec::with_capacity(file.vessels.len());
+    for (i, spec) in file.vessels.iter().enumerate() {
+        let what = format!("Vessel {}", i + 1);
+        let (position, velocity) = resolve_state(&what, spec.position, spec.velocity, spec.orbit.as_ref(), ephemeris_state(&what, spec.ephemeris.as_deref(), &imported)?, &planets, big_gravity)?;
+        let mut vessel = Player::new(position, velocity, spec.mass, spec.rotation);
+        if let Some(area_to_mass) = spec.sail_area_to_mass {
+            vessel = vessel.with_sail(area_to_mass);
+        }
+        if let Some(thrust) = spec.engine_thrust {
+            vessel = vessel.with_engine_thrust(thrust);
+        }
+        vessels.push(vessel);
+    }
+
+    Ok(Game::with_gravity(planets, vessels, big_gravity, units))
+}
+
+/// Load a scenario file from disk, with texture and ephemeris paths relative to the file
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_scenario_file(path: &str) -> Result<Game, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read scenario {}: {}", path, e))?;
+    let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+
+    let resolve = |texture: &str| directory.join(texture).to_string_lossy().into_owned();
+    let read_table = |table: &str| std::fs::read_to_string(resolve(table)).map_err(|e| e.to_string());
+    let mut game = load_scenario(&source, |texture| Texture::load_from_file(&resolve(texture)).map(Some), read_table)
+        .map_err(|e| format!("{}: {}", path, e))?;
+
+    // Record texture sources as they resolve from here, not from the scenario file
+    for planet in &mut game.planets {
+        planet.texture_source = planet.texture_source.as_deref().map(resolve);
+    }
+    Ok(game)
+}
@@ -0,0 +1,91 @@
+//! Scenario files that cannot be built are refused with a message saying why.
+
+use crate::scenario::load_scenario;
+use crate::texture::Texture;
+
+const VESSEL: &str = r#"
+    [[vessels]]
+    mass = 1.0
+    position = [0.0, 1000.0]
+"#;
+
+/// The error from loading bodies plus a vessel, with textures from `load_texture`
+fn refusal(bodies: &str, load_texture: impl Fn(&str) -> Result<Option<Texture>, String>) -> String {
+    let source = format!("{}{}", bodies, VESSEL);
+    match load_scenario(&source, load_texture, |path| Err(format!("no table {}", path))) {
+        Ok(_) => panic!("scenario was accepted"),
+        Err(message) => message,
+    }
+}
+
+fn no_texture(_: &str) -> Result<Option<Texture>, String> {
+    Ok(None)
+}
+
+#[test]
+fn unknown_parent_is_refused() {
+    let bodies = r#"
+        [[bodies]]
+        name = "Moon"
+        radius = 10.0
+        mass = 1.0
+        orbit = { parent = "Nowhere", periapsis = 500.0 }
+        color = 0xFFFFFF
+    "#;
+    assert_eq!(refusal(bodies, no_texture), "Body 'Moon' orbits unknown parent 'Nowhere' (parents must be listed before what orbits them)");
+}
+
+#[test]
+fn parent_listed_after_its_child_is_refused() {
+    let bodies = r#"
+        [[bodies]]
+        name = "Moon"
+        radius = 10.0
+        mass = 1.0
+        orbit = { parent = "Planet", periapsis = 500.0 }
+        color = 0xFFFFFF
+
+        [[bodies]]
+        name = "Planet"
+        radius = 100.0
+        mass = 1e12
+        position = [0.0, 0.0]
+        color = 0x4040FF
+    "#;
+    assert_eq!(refusal(bodies, no_texture), "Body 'Moon' orbits unknown parent 'Planet' (parents must be listed before what orbits them)");
+}
+
+#[test]
+fn duplicate_body_is_refused() {
+    let bodies = r#"
+        [[bodies]]
+        name = "Planet"
+        radius = 100.0
+        mass = 1e12
+        position = [0.0, 0.0]
+        color = 0x4040FF
+
+        [[bodies]]
+        name = "Planet"
+        radius = 100.0
+        mass = 1e12
+        position = [5000.0, 0.0]
+        color = 0x4040FF
+    "#;
+    assert_eq!(refusal(bodies, no_texture), "Body 'Planet' is defined twice");
+}
+
+#[test]
+fn failing_texture_loader_is_reported() {
+    let bodies = r#"
+        [[bodies]]
+        name = "Planet"
+        radius = 100.0
+        mass = 1e12
+        position = [0.0, 0.0]
+        color = 0x4040FF
+        texture = "planet.png"
+    "#;
+    let message = refusal(bodies, |path| Err(format!("cannot decode {}", path)));
+    assert_eq!(message, "Body 'Planet' has a bad texture: cannot decode planet.png");
+}
@@ -0,0 +1,494 @@
+//! Headless simulator: runs a scenario without a window, for batch experiments
+//! over SSH and in tests. It shares the game's modules but drives only the
+//! simulation.
+
+mod planet;
+mod vector2;
+mod player;
+mod initial_universe;
+mod texture;
+mod det_math;
+mod units;
+mod tidal;
+mod scenario;
+mod generator;
+mod integrator;
+mod burns;
+mod conservation;
+mod stability;
+mod font;
+mod sprite_renderer;
+mod flyby;
+// Shared with the window, whose controls, saving and recording go unused here;
+// rendering needs them for the HUD
+mod game;
+mod docking;
+mod history;
+mod save;
+mod ephemeris;
+mod render;
+mod lagrange;
+mod quicksave;
+mod replay;
+mod ghost;
+mod keyboard_input;
+mod offscreen;
+mod capture;
+
+#[cfg(test)]
+mod burns_tests;
+#[cfg(test)]
+mod docking_tests;
+#[cfg(test)]
+mod ephemeris_tests;
+#[cfg(test)]
+mod golden_tests;
+#[cfg(test)]
+mod history_tests;
+#[cfg(test)]
+mod integrator_tests;
+#[cfg(test)]
+mod orbit_tests;
+#[cfg(test)]
+mod replay_tests;
+#[cfg(test)]
+mod save_tests;
+#[cfg(test)]
+mod scenario_tests;
+#[cfg(test)]
+mod surface_tests;
+#[cfg(test)]
+mod tidal_tests;
+#[cfg(test)]
+mod trajectory_tests;
+
+use crate::burns::BurnSchedule;
+use crate::capture::{Capture, CaptureOptions, DEFAULT_CAPTURE_DELAY_MS, DEFAULT_CAPTURE_INTERVAL};
+use crate::conservation::{measure, relative_change, Conserved};
+use crate::docking::docking_status;
+use crate::ephemeris::{body_by_name, csv_field, Ephemeris, EphemerisFormat, EphemerisOptions, DEFAULT_EPHEMERIS_DECIMATION};
+use crate::game::{Game, TRAJECTORY_DT, TRAJECTORY_SUBSTEPS};
+use crate::initial_universe::scenario_by_name;
+use crate::integrator::Integrator;
+use crate::offscreen::{parse_frame, parse_size, render_to_file, DEFAULT_RENDER_HEIGHT, DEFAULT_RENDER_WIDTH, MIN_RENDER_HEIGHT, MIN_RENDER_WIDTH};
+use crate::planet::BodyRole;
+use crate::render::Camera;
+use crate::save::load_game_file;
+use crate::scenario::load_scenario_file;
+use crate::stability::{analyze_stability, DEFAULT_ANALYSIS_SPAN};
+use crate::vector2::Vector2;
+use serde::Serialize;
+use std::collections::HashSet;
+
+const USAGE: &str = "Usage: spaceflight_sim <scenario name | scenario.toml | game.sav> [--steps N] [--dt SECONDS] \
+                     [--integrator euler|leapfrog|rk4] [--burns burns.toml] [--output report.json|states.csv] \
+                     [--render final.png] [--capture run.gif|frame.png [--every N] [--delay MS] [--look-ahead STEPS]] \
+                     [--size WIDTHxHEIGHT] [--zoom Z] [--frame absolute|relative|body:NAME|rotating:A,B]\n       \
+                     spaceflight_sim <scenario name | scenario.toml | game.sav> --analyze [--span SECONDS]\n       \
+                     spaceflight_sim <scenario name | scenario.toml | game.sav> --ephemeris out.csv|out.json [--decimation N] [--relative-to BODY]";
+
+// Updates run when --steps is not given
+const DEFAULT_STEPS: usize = 1000;
+
+// Predicted steps drawn in each captured frame; a tenth of the window's, since
+// every captured frame predicts afresh
+const DEFAULT_CAPTURE_LOOK_AHEAD: usize = 10000;
+
+struct Options {
+    scenario: String,
+    steps: usize,
+    dt: f64,
+    integrator: Integrator,
+    burns: Option<String>,
+    output: Option<String>,
+    render: Option<String>,  // Image of the final state
+    size: (usize, usize),
+    zoom: f64,
+    frame: Option<String>,   // Resolved against the game at each render
+    capture: Option<String>, // Animation of the run
+    capture_interval: usize,
+    capture_delay: u32,
+    look_ahead: usize, // Prediction steps behind each captured frame
+    analyze: bool, // Report on the scenario's long-term stability instead of running it
+    span: f64,     // Game seconds the stability analysis covers
+    ephemeris: Option<String>, // Export the predicted trajectories instead of running
+    decimation: usize,
+    relative_to: Option<String>, // Body the ephemeris is taken relative to
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        scenario: String::new(),
+        steps: DEFAULT_STEPS,
+        dt: TRAJECTORY_DT / TRAJECTORY_SUBSTEPS as f64, // One physics substep of the game
+        integrator: Integrator::SemiImplicitEuler,
+        burns: None,
+        output: None,
+        render: None,
+        size: (DEFAULT_RENDER_WIDTH, DEFAULT_RENDER_HEIGHT),
+        zoom: 1.0,
+        frame: None,
+        capture: None,
+        capture_interval: DEFAULT_CAPTURE_INTERVAL,
+        capture_delay: DEFAULT_CAPTURE_DELAY_MS,
+        look_ahead: DEFAULT_CAPTURE_LOOK_AHEAD,
+        analyze: false,
+        span: DEFAULT_ANALYSIS_SPAN,
+        ephemeris: None,
+        decimation: DEFAULT_EPHEMERIS_DECIMATION,
+        relative_to: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--steps" => options.steps = value()?.parse().map_err(|_| "--steps needs a whole number".to_string())?,
+            "--dt" => options.dt = value()?.parse().map_err(|_| "--dt needs a number of game seconds".to_string())?,
+            "--integrator" => options.integrator = Integrator::parse(value()?)?,
+            "--burns" => options.burns = Some(value()?.clone()),
+            "--output" => options.output = Some(value()?.clone()),
+            "--render" => options.render = Some(value()?.clone()),
+            "--size" => options.size = parse_size(value()?)?,
+            "--zoom" => options.zoom = value()?.parse().map_err(|_| "--zoom needs a number".to_string())?,
+            "--frame" => options.frame = Some(value()?.clone()),
+            "--capture" => options.capture = Some(value()?.clone()),
+            "--every" => options.capture_interval = value()?.parse().map_err(|_| "--every needs a whole number".to_string())?,
+            "--delay" => options.capture_delay = value()?.parse().map_err(|_| "--delay needs a whole number of milliseconds".to_string())?,
+            "--look-ahead" => options.look_ahead = value()?.parse().map_err(|_| "--look-ahead needs a whole number of steps".to_string())?,
+            "--analyze" => options.analyze = true,
+            "--span" => options.span = value()?.parse().map_err(|_| "--span needs a number of game seconds".to_string())?,
+            "--ephemeris" => options.ephemeris = Some(value()?.clone()),
+            "--decimation" => options.decimation = value()?.parse().map_err(|_| "--decimation needs a whole number".to_string())?,
+            "--relative-to" => options.relative_to = Some(value()?.clone()),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if options.scenario.is_empty() => options.scenario = arg.clone(),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+
+    if options.scenario.is_empty() {
+        return Err("No scenario given".to_string());
+    }
+    if options.dt.is_nan() || options.dt <= 0.0 {
+        return Err("--dt must be positive".to_string());
+    }
+    if options.size.0 < MIN_RENDER_WIDTH || options.size.1 < MIN_RENDER_HEIGHT {
+        return Err(format!("--size must be at least {}x{}", MIN_RENDER_WIDTH, MIN_RENDER_HEIGHT));
+    }
+    if options.capture_interval == 0 {
+        return Err("--every must be at least 1".to_string());
+    }
+    if options.span.is_nan() || options.span <= 0.0 {
+        return Err("--span must be positive".to_string());
+    }
+    if options.decimation == 0 {
+        return Err("--decimation must be at least 1".to_string());
+    }
+    if let Some(path) = &options.ephemeris {
+        if options.analyze {
+            return Err("--analyze and --ephemeris can't be combined".to_string());
+        }
+        if EphemerisFormat::from_path(path).is_none() {
+            return Err(format!("Ephemeris {} must end in .csv or .json", path));
+        }
+    }
+    Ok(options)
+}
+
+/// A saved game, a scenario file, or a built-in scenario by name
+fn load(scenario: &str) -> Result<Game, String> {
+    if scenario.ends_with(".sav") {
+        load_game_file(scenario).map(|(game, _)| game)
+    } else if scenario.ends_with(".toml") {
+        load_scenario_file(scenario)
+    } else {
+        scenario_by_name(scenario).ok_or_else(|| format!("Unknown scenario '{}'", scenario))
+    }
+}
+
+#[derive(Serialize)]
+struct BodyState {
+    name: String,
+    position: Vector2,
+    velocity: Vector2,
+}
+
+#[derive(Serialize)]
+struct Event {
+    time: f64,
+    description: String,
+}
+
+#[derive(Serialize)]
+struct Report {
+    scenario: String,
+    integrator: &'static str,
+    steps: usize,
+    dt: f64,
+    time: f64,
+    bodies: Vec<BodyState>,
+    vessels: Vec<BodyState>,
+    events: Vec<Event>,
+    initial: Conserved,
+    last: Conserved,
+}
+
+/// What changes between steps that is worth reporting
+struct Watch {
+    planets: Vec<String>,
+    landed: Vec<Option<usize>>,
+    collisions: HashSet<(String, String)>, // Pairs already reported, until they separate
+}
+
+impl Watch {
+    /// Start from the game as it is, so nothing already true is reported
+    fn new(game: &Game) -> Self {
+        let mut watch = Self { planets: Vec::new(), landed: Vec::new(), collisions: HashSet::new() };
+        watch.observe(game, 0.0, &mut Vec::new());
+        watch
+    }
+
+    fn observe(&mut self, game: &Game, time: f64, events: &mut Vec<Event>) {
+        let mut report = |description: String| events.push(Event { time, description });
+        let planets: Vec<String> = game.planets.iter().map(|planet| planet.name.clone()).collect();
+        for name in self.planets.iter().filter(|name| !planets.contains(name)) {
+            report(format!("{} was torn apart", name));
+        }
+
+        let landed: Vec<Option<usize>> = game.vessels.iter().map(|vessel| vessel.landed.map(|contact| contact.planet_index)).collect();
+        // Docking renumbers the vessels, and the run reports it as it happens
+        if landed.len() == self.landed.len() && planets == self.planets {
+            for (i, (before, after)) in self.landed.iter().zip(&landed).enumerate() {
+                match (before, after) {
+                    (None, Some(body)) if game.vessels[i].landed.is_some_and(|contact| contact.crashed) => {
+                        report(format!("Vessel {} crashed on {}", i + 1, planets[*body]))
+                    }
+                    (None, Some(body)) => report(format!("Vessel {} landed on {}", i + 1, planets[*body])),
+                    (Some(body), None) => report(format!("Vessel {} lifted off {}", i + 1, planets[*body])),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut touching = HashSet::new();
+        for (i, a) in game.planets.iter().enumerate() {
+            for b in &game.planets[i + 1..] {
+                if a.role != BodyRole::Barycenter && b.role != BodyRole::Barycenter && a.position.distance(&b.position) < a.radius + b.radius {
+                    touching.insert((a.name.clone(), b.name.clone()));
+                }
+            }
+        }
+        for (a, b) in touching.difference(&self.collisions) {
+            report(format!("{} collided with {}", a, b));
+        }
+
+        self.planets = planets;
+        self.landed = landed;
+        self.collisions = touching;
+    }
+}
+
+/// The camera for the game as it is now; bodies are looked up by name each time
+/// since tidal disruption can renumber them
+fn camera(options: &Options, game: &Game) -> Result<Camera, String> {
+    let camera = Camera::new(options.size.0, options.size.1).with_zoom(options.zoom);
+    match &options.frame {
+        Some(frame) => Ok(camera.with_frame(parse_frame(game, frame)?)),
+        None => Ok(camera),
+    }
+}
+
+/// Render a captured frame if one is due. Predictions are remade first, since
+/// stepping the game here does not advance them.
+fn capture_frame(capture: &mut Option<Capture>, options: &Options, game: &mut Game) -> Result<(), String> {
+    if let Some(capture) = capture
+        && capture.next_frame()
+    {
+        // Only the drawn paths depend on the look-ahead, so the run itself is unaffected
+        let prediction_steps = std::mem::replace(&mut game.prediction_steps, options.look_ahead);
+        game.recalculate_trajectories();
+        game.prediction_steps = prediction_steps;
+        capture.write(game, &camera(options, game)?)?;
+    }
+    Ok(())
+}
+
+fn run(options: &Options) -> Result<Report, String> {
+    let mut game = load(&options.scenario)?;
+    let burns = options.burns.as_deref().map(BurnSchedule::load_file).transpose()?;
+    let mut capture = match &options.capture {
+        Some(path) => {
+            let capture_options = CaptureOptions::new().with_interval(options.capture_interval).with_delay(options.capture_delay);
+            Some(Capture::start(path, options.size.0, options.size.1, capture_options)?)
+        }
+        None => None,
+    };
+
+    let initial = measure(&game);
+    let mut events = Vec::new();
+    let mut watch = Watch::new(&game);
+    capture_frame(&mut capture, options, &mut game)?;
+    for step in 0..options.steps {
+        let time = step as f64 * options.dt;
+        if let Some(burns) = &burns {
+            burns.apply(&mut game, time, options.dt);
+        }
+        options.integrator.advance(&mut game, options.dt);
+
+        // The same checks the game makes after every tick. Docking removes the
+        // target and can renumber the chaser, so both are named beforehand.
+        let chaser = game.active_vessel;
+        let target = docking_status(&game).map(|status| status.target);
+        if game.try_dock() && let Some(target) = target {
+            let description = format!("Vessel {} docked with vessel {}", chaser + 1, target + 1);
+            events.push(Event { time: time + options.dt, description });
+        }
+        game.check_tidal_disruption();
+        watch.observe(&game, time + options.dt, &mut events);
+        capture_frame(&mut capture, options, &mut game)?;
+    }
+    if let Some(capture) = capture {
+        println!("{}", capture.finish()?);
+    }
+
+    if let Some(path) = &options.render {
+        game.recalculate_trajectories();
+        // Predictions from the final state, as the window would show them
+        render_to_file(&game, &camera(options, &game)?, path)?;
+    }
+
+    let state = |name: String, position, velocity| BodyState { name, position, velocity };
+    Ok(Report {
+        scenario: options.scenario.clone(),
+        integrator: options.integrator.name(),
+        steps: options.steps,
+        dt: options.dt,
+        time: options.steps as f64 * options.dt,
+        bodies: game.planets.iter().map(|planet| state(planet.name.clone(), planet.position, planet.velocity)).collect(),
+        vessels: game.vessels.iter().enumerate().map(|(i, vessel)| state(format!("Vessel {}", i + 1), vessel.position, vessel.velocity)).collect(),
+        events,
+        initial,
+        last: measure(&game),
+    })
+}
+
+/// Integrate the scenario's bodies alone and describe how well the system holds together
+fn analyze(options: &Options) -> Result<String, String> {
+    let game = load(&options.scenario)?;
+    let report = analyze_stability(&game, options.span);
+    Ok(stability::format_report(&game, &report))
+}
+
+/// Predict the scenario from its start and write the ephemeris
+fn export_ephemeris(options: &Options, path: &str) -> Result<String, String> {
+    let mut game = load(&options.scenario)?;
+    let frame = match &options.relative_to {
+        Some(name) => Some(body_by_name(&game, name).ok_or_else(|| format!("No body named '{}' in {}", name, options.scenario))?),
+        None => None,
+    };
+    game.recalculate_trajectories();
+    let ephemeris_options = EphemerisOptions::new().with_decimation(options.decimation).relative_to(frame);
+    Ephemeris::from_game(&game, &ephemeris_options)?.save_file(path)?;
+    Ok(format!("Wrote ephemeris to {}", path))
+}
+
+/// Final states as CSV, one row per body then per vessel
+fn states_csv(report: &Report) -> String {
+    let mut csv = String::from("kind,name,x,y,vx,vy\n");
+    let rows = report.bodies.iter().map(|state| ("body", state)).chain(report.vessels.iter().map(|state| ("vessel", state)));
+    for (kind, state) in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            kind, csv_field(&state.name), state.position.x, state.position.y, state.velocity.x, state.velocity.y
+        ));
+    }
+    csv
+}
+
+/// Human-readable summary for the terminal
+fn format_report(report: &Report) -> String {
+    let mut lines = vec![format!(
+        "{}: {} steps of {} s with {} ({} s of game time)",
+        report.scenario, report.steps, report.dt, report.integrator, report.time
+    )];
+
+    lines.push("Final states:".to_string());
+    for state in report.bodies.iter().chain(&report.vessels) {
+        lines.push(format!(
+            "  {:<12} position ({:.6e}, {:.6e}) velocity ({:.6e}, {:.6e})",
+            state.name, state.position.x, state.position.y, state.velocity.x, state.velocity.y
+        ));
+    }
+
+    lines.push(if report.events.is_empty() { "Events: none".to_string() } else { "Events:".to_string() });
+    for event in &report.events {
+        lines.push(format!("  t = {:.3} s: {}", event.time, event.description));
+    }
+
+    let (initial, last) = (&report.initial, &report.last);
+    lines.push("Conservation:".to_string());
+    lines.push(format!("  Energy           {:.9e} -> {:.9e} (relative change {:.3e})", initial.energy, last.energy, relative_change(initial.energy, last.energy)));
+    lines.push(format!(
+        "  Momentum         ({:.6e}, {:.6e}) -> ({:.6e}, {:.6e}) (change {:.3e})",
+        initial.momentum.x,
+        initial.momentum.y,
+        last.momentum.x,
+        last.momentum.y,
+        last.momentum.subtract(&initial.momentum).magnitude()
+    ));
+    lines.push(format!(
+        "  Angular momentum {:.9e} -> {:.9e} (relative change {:.3e})",
+        initial.angular_momentum,
+        last.angular_momentum,
+        relative_change(initial.angular_momentum, last.angular_momentum)
+    ));
+    lines.join("\n")
+}
+
+fn write_output(report: &Report, path: &str) -> Result<(), String> {
+    let text = if path.ends_with(".json") {
+        serde_json::to_string_pretty(report).map_err(|e| format!("Failed to write report: {}", e))?
+    } else if path.ends_with(".csv") {
+        states_csv(report)
+    } else {
+        return Err(format!("Output {} must end in .json or .csv", path));
+    };
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = parse_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+
+    let headless = match &options.ephemeris {
+        Some(path) => Some(export_ephemeris(&options, path)),
+        None if options.analyze => Some(analyze(&options)),
+        None => None,
+    };
+    if let Some(result) = headless {
+        match result {
+            Ok(report) => println!("{}", report),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let result = run(&options).and_then(|report| {
+        if let Some(path) = &options.output {
+            write_output(&report, path)?;
+        }
+        println!("{}", format_report(&report));
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,4 +1,5 @@
-/// Sprite rendering module for drawing textured circles
+//! Sprite rendering module for drawing textured circles
+
 use crate::texture::Texture;
 
 /// Draw a circular sprite (texture mapped onto a circle)
@@ -36,16 +37,15 @@ pub fn draw_circular_sprite(
                             buffer[idx] = color & 0xFFFFFF; // Remove alpha channel
                         } else {
                             // Alpha blending
-                            let src_r = ((color >> 16) & 0xFF) as u32;
-                            let src_g = ((color >> 8) & 0xFF) as u32;
-                            let src_b = (color & 0xFF) as u32;
+                            let src_r = (color >> 16) & 0xFF;
+                            let src_g = (color >> 8) & 0xFF;
+                            let src_b = color & 0xFF;
 
                             let dst = buffer[idx];
-                            let dst_r = ((dst >> 16) & 0xFF) as u32;
-                            let dst_g = ((dst >> 8) & 0xFF) as u32;
-                            let dst_b = (dst & 0xFF) as u32;
+                            let dst_r = (dst >> 16) & 0xFF;
+                            let dst_g = (dst >> 8) & 0xFF;
+                            let dst_b = dst & 0xFF;
 
-                            let alpha = alpha as u32;
                             let inv_alpha = 255 - alpha;
 
                             let r = (src_r * alpha + dst_r * inv_alpha) / 255;
@@ -0,0 +1,233 @@
+//! Headless long-horizon stability analysis of a scenario
+
+use crate::det_math;
+use crate::game::{CachedTrajectories, Game, TRAJECTORY_DT};
+use crate::history::StateHistory;
+use crate::planet::BodyRole;
+use crate::vector2::Vector2;
+
+// Game seconds integrated when no span is given
+pub const DEFAULT_ANALYSIS_SPAN: f64 = 3600.0;
+
+// Separation the shadow system starts from and is renormalized back to
+const SHADOW_SEPARATION: f64 = 1e-6;
+
+// Steps between Lyapunov renormalizations
+const RENORMALIZE_INTERVAL: usize = 10;
+
+/// Two-body osculating elements of a body around its primary
+#[derive(Clone, Copy)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f64, // Negative once unbound
+    pub eccentricity: f64,
+}
+
+/// How one body's orbit changed over the run
+pub struct ElementDrift {
+    pub body: usize,
+    pub primary: usize,
+    pub initial: OrbitalElements,
+    pub last: OrbitalElements,
+    pub max_semi_major_axis_change: f64, // Largest |da| / a0 seen
+}
+
+pub struct StabilityReport {
+    pub span: f64,
+    pub first_ejection: Option<(f64, usize, usize)>, // (time, body, primary it escaped)
+    pub first_collision: Option<(f64, usize, usize)>, // (time, body, body)
+    pub drift: Vec<ElementDrift>,
+    pub lyapunov_exponent: f64, // Finite-time estimate, 1 / time
+}
+
+impl StabilityReport {
+    /// Lyapunov time, or None when no divergence was measured
+    pub fn lyapunov_time(&self) -> Option<f64> {
+        (self.lyapunov_exponent > 0.0).then(|| 1.0 / self.lyapunov_exponent)
+    }
+}
+
+/// Bodies only, without vessels, history or prediction cache
+fn headless_copy(game: &Game) -> Game {
+    Game {
+        big_gravity: game.big_gravity,
+        units: game.units,
+        planets: game.planets.clone(),
+        vessels: Vec::new(),
+        active_vessel: 0,
+        tick: game.tick,
+        history: StateHistory::new(0), // Analysis keeps no history
+        cached_trajectories: CachedTrajectories::new(),
+        prediction_steps: 0,
+    }
+}
+
+fn orbital_elements(game: &Game, body: usize, primary: usize) -> OrbitalElements {
+    let (b, p) = (&game.planets[body], &game.planets[primary]);
+    let mu = game.big_gravity * (b.mass + p.mass);
+    let r = b.position.subtract(&p.position);
+    let v = b.velocity.subtract(&p.velocity);
+
+    let energy = v.dot(&v) / 2.0 - mu / r.magnitude();
+    let h = r.cross(&v);
+    OrbitalElements {
+        semi_major_axis: -mu / (2.0 * energy),
+        eccentricity: (1.0 + 2.0 * energy * h * h / (mu * mu)).max(0.0).sqrt(),
+    }
+}
+
+/// Hill radius of a body around whatever it orbits, or None for the root of the system
+fn hill_radius(game: &Game, body: usize) -> Option<f64> {
+    let primary = game.reference_body(body)?;
+    let (b, p) = (&game.planets[body], &game.planets[primary]);
+    if p.role == BodyRole::Barycenter {
+        return None;
+    }
+    Some(b.position.distance(&p.position) * det_math::cbrt(b.mass / (3.0 * p.mass)))
+}
+
+/// Phase-space distance between the bodies of two copies of a system
+fn separation(a: &Game, b: &Game) -> f64 {
+    a.planets
+        .iter()
+        .zip(&b.planets)
+        .map(|(p, q)| {
+            let dr = p.position.subtract(&q.position);
+            let dv = p.velocity.subtract(&q.velocity);
+            dr.dot(&dr) + dv.dot(&dv)
+        })
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Integrate a scenario's bodies for `span` game seconds and report how well it holds together
+pub fn analyze_stability(game: &Game, span: f64) -> StabilityReport {
+    let mut system = headless_copy(game);
+
+    // Each body is judged against the primary it started out orbiting
+    let primaries: Vec<Option<usize>> = (0..system.planets.len())
+        .map(|i| {
+            system.reference_body(i).filter(|&p| system.planets[p].role != BodyRole::Barycenter && system.planets[i].role != BodyRole::Barycenter)
+        })
+        .collect();
+    let mut drift: Vec<ElementDrift> = primaries
+        .iter()
+        .enumerate()
+        .filter_map(|(body, primary)| {
+            let primary = (*primary)?;
+            let initial = orbital_elements(&system, body, primary);
+            Some(ElementDrift { body, primary, initial, last: initial, max_semi_major_axis_change: 0.0 })
+        })
+        .collect();
+
+    // Shadow system nudged off the real one, for the Lyapunov estimate
+    let mut shadow = headless_copy(game);
+    let nudge = SHADOW_SEPARATION / (shadow.planets.len() as f64).sqrt();
+    for planet in shadow.planets.iter_mut().filter(|planet| planet.role != BodyRole::Barycenter) {
+        planet.position = planet.position.add(&Vector2 { x: nudge, y: 0.0 });
+    }
+    let initial_separation = separation(&system, &shadow);
+    let mut log_growth = 0.0;
+
+    let mut first_ejection = None;
+    let mut first_collision = None;
+    let steps = (span / TRAJECTORY_DT) as usize;
+
+    for step in 1..=steps {
+        system.step();
+        shadow.step();
+        let time = step as f64 * TRAJECTORY_DT;
+
+        if step % RENORMALIZE_INTERVAL == 0 {
+            // Benettin renormalization: accumulate growth, pull the shadow back in
+            let distance = separation(&system, &shadow);
+            if distance > 0.0 {
+                log_growth += (distance / initial_separation).ln();
+                let shrink = initial_separation / distance;
+                for (real, nudged) in system.planets.iter().zip(shadow.planets.iter_mut()) {
+                    nudged.position = real.position.add(&nudged.position.subtract(&real.position).scale(shrink));
+                    nudged.velocity = real.velocity.add(&nudged.velocity.subtract(&real.velocity).scale(shrink));
+                }
+            }
+        }
+
+        for entry in &mut drift {
+            let elements = orbital_elements(&system, entry.body, entry.primary);
+            let change = ((elements.semi_major_axis - entry.initial.semi_major_axis) / entry.initial.semi_major_axis).abs();
+            entry.max_semi_major_axis_change = entry.max_semi_major_axis_change.max(change);
+            entry.last = elements;
+
+            // Unbound and out of the primary's reach
+            if first_ejection.is_none() && elements.semi_major_axis < 0.0 {
+                let distance = system.planets[entry.body].position.distance(&system.planets[entry.primary].position);
+                if hill_radius(&system, entry.primary).is_none_or(|reach| distance > reach) {
+                    first_ejection = Some((time, entry.body, entry.primary));
+                }
+            }
+        }
+
+        if first_collision.is_none() {
+            'pairs: for i in 0..system.planets.len() {
+                for j in (i + 1)..system.planets.len() {
+                    let (a, b) = (&system.planets[i], &system.planets[j]);
+                    if a.role == BodyRole::Barycenter || b.role == BodyRole::Barycenter {
+                        continue;
+                    }
+                    if a.position.distance(&b.position) < a.radius + b.radius {
+                        first_collision = Some((time, i, j));
+                        break 'pairs;
+                    }
+                }
+            }
+        }
+    }
+
+    let elapsed = steps as f64 * TRAJECTORY_DT;
+    StabilityReport {
+        span: elapsed,
+        first_ejection,
+        first_collision,
+        drift,
+        lyapunov_exponent: if elapsed > 0.0 { log_growth / elapsed } else { 0.0 },
+    }
+}
+
+/// Human-readable summary for the command line
+pub fn format_report(game: &Game, report: &StabilityReport) -> String {
+    let units = &game.units;
+    let name = |i: usize| game.planets[i].name.as_str();
+    let mut lines = vec![format!("Stability over {:.0} s of game time", report.span)];
+
+    lines.push(match report.first_ejection {
+        Some((time, body, primary)) => format!("First ejection: {} escaped {} at t = {:.1} s", name(body), name(primary), time),
+        None => "First ejection: none".to_string(),
+    });
+    lines.push(match report.first_collision {
+        Some((time, a, b)) => format!("First collision: {} and {} at t = {:.1} s", name(a), name(b), time),
+        None => "First collision: none".to_string(),
+    });
+
+    lines.push("Orbital element drift:".to_string());
+    for entry in &report.drift {
+        let describe = |elements: &OrbitalElements| {
+            if elements.semi_major_axis < 0.0 {
+                format!("unbound (e {:.3})", elements.eccentricity)
+            } else {
+                format!("a {} e {:.3}", units.format_length(elements.semi_major_axis), elements.eccentricity)
+            }
+        };
+        lines.push(format!(
+            "  {} around {}: {} -> {}, max |da|/a {:.2e}",
+            name(entry.body),
+            name(entry.primary),
+            describe(&entry.initial),
+            describe(&entry.last),
+            entry.max_semi_major_axis_change,
+        ));
+    }
+
+    lines.push(match report.lyapunov_time() {
+        Some(time) => format!("Lyapunov exponent: {:.3e} /s (Lyapunov time {:.1} s)", report.lyapunov_exponent, time),
+        None => "Lyapunov exponent: no divergence measured".to_string(),
+    });
+    lines.join("\n")
+}
@@ -0,0 +1,36 @@
+//! Touchdown on a planet surface: gentle descents land, fast ones crash.
+
+use crate::game::Game;
+use crate::planet::Planet;
+use crate::player::{Player, CRASH_SPEED_FRACTION, PLAYER_FOOTPRINT};
+use crate::vector2::Vector2;
+
+const RADIUS: f64 = 100.0;
+const MASS: f64 = 1.0e9;
+
+/// A vessel just above a resting planet, falling straight down at the given
+/// multiple of the crash speed, one step later
+fn touchdown(crash_speeds: f64) -> Game {
+    let planet = Planet::new("Rock".to_string(), RADIUS, MASS, Vector2 { x: 0.0, y: 0.0 }, Vector2 { x: 0.0, y: 0.0 }, 0x808080);
+    let altitude = RADIUS + PLAYER_FOOTPRINT + 0.001;
+    let vessel = Player::new(Vector2 { x: 0.0, y: altitude }, Vector2 { x: 0.0, y: 0.0 }, 1.0, 0.0);
+    let mut game = Game::new(vec![planet], vec![vessel]);
+    let crash_speed = CRASH_SPEED_FRACTION * (game.big_gravity * MASS / (RADIUS + PLAYER_FOOTPRINT)).sqrt();
+    game.vessels[0].velocity = Vector2 { x: 0.0, y: -crash_speeds * crash_speed };
+    game.step();
+    game
+}
+
+#[test]
+fn gentle_touchdown_lands() {
+    let game = touchdown(0.5);
+    let contact = game.vessels[0].landed.expect("vessel touched down");
+    assert!(!contact.crashed);
+}
+
+#[test]
+fn fast_impact_crashes() {
+    let game = touchdown(2.0);
+    let contact = game.vessels[0].landed.expect("vessel hit the surface");
+    assert!(contact.crashed);
+}
@@ -1,4 +1,5 @@
-/// Texture management module
+//! Texture management module
+
 use image::{DynamicImage, GenericImageView, Rgba};
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,120 @@
+//! Tidal disruption of bodies that cross inside another body's Roche limit
+
+use crate::det_math;
+use crate::game::Game;
+use crate::planet::Planet;
+use crate::vector2::Vector2;
+
+// Number of pieces a disrupted body breaks into
+const FRAGMENT_COUNT: usize = 4;
+
+// Bodies smaller than this hold together as rubble and are not broken up further
+const MIN_DISRUPTION_RADIUS: f64 = 10.0;
+
+/// Mean density, treating the body as a sphere
+fn density(body: &Planet) -> f64 {
+    body.mass / (4.0 / 3.0 * std::f64::consts::PI * body.radius.powi(3))
+}
+
+/// Rigid-body Roche limit of a secondary around a primary:
+/// d = R_primary * (2 * density_primary / density_secondary)^(1/3)
+pub fn roche_limit(primary: &Planet, secondary: &Planet) -> f64 {
+    primary.radius * det_math::cbrt(2.0 * density(primary) / density(secondary))
+}
+
+/// Break a body into equal-mass fragments strung out along the tidal axis.
+/// The fragments keep the original center of mass and momentum and co-rotate
+/// with the body's current orbit around the primary. They share the body's
+/// density and so start inside the same Roche limit; they are marked fresh so
+/// they aren't broken up again at once.
+fn fragment(body: &Planet, primary: &Planet) -> Vec<Planet> {
+    let count = FRAGMENT_COUNT as f64;
+    let mass = body.mass / count;
+    let radius = body.radius / det_math::cbrt(count); // Same total volume
+
+    let offset = body.position.subtract(&primary.position);
+    let distance = offset.magnitude();
+    let axis = offset.scale(1.0 / distance);
+
+    // Angular velocity of the body around the primary
+    let relative_velocity = body.velocity.subtract(&primary.velocity);
+    let omega = offset.cross(&relative_velocity) / (distance * distance);
+
+    let spacing = 2.1 * radius;
+    (0..FRAGMENT_COUNT)
+        .map(|k| {
+            let arm = axis.scale((k as f64 - (count - 1.0) / 2.0) * spacing);
+            let position = body.position.add(&arm);
+            let velocity = body.velocity.add(&Vector2 { x: -omega * arm.y, y: omega * arm.x });
+
+            let piece = Planet::new(
+                format!("{} {}", body.name, (b'A' + k as u8) as char),
+                radius,
+                mass,
+                position,
+                velocity,
+                fragment_color(body.color, k),
+            )
+            .with_description(format!("A fragment of {}, torn apart by the tides of {}.", body.name, primary.name))
+            .with_role(body.role);
+            Planet { fresh_fragment: true, ..piece }
+        })
+        .collect()
+}
+
+/// Vary the parent's color so fragments can be told apart
+fn fragment_color(color: u32, index: usize) -> u32 {
+    let shade = 0.7 + 0.15 * index as f64;
+    let channel = |shift: u32| ((((color >> shift) & 0xFF) as f64 * shade).min(255.0) as u32) << shift;
+    channel(16) | channel(8) | channel(0)
+}
+
+impl Game {
+    /// The first more massive body whose Roche limit a planet is inside
+    fn roche_primary(&self, s: usize) -> Option<usize> {
+        let secondary = &self.planets[s];
+        self.planets.iter().enumerate().position(|(p, primary)| {
+            let distance = primary.position.distance(&secondary.position);
+            p != s && primary.mass > secondary.mass && distance > 0.0 && distance < roche_limit(primary, secondary)
+        })
+    }
+
+    /// Fragment the first body found inside another's Roche limit. The first
+    /// fragment takes the body's place in the list, so indices of other bodies
+    /// stay valid; the rest are appended and the prediction cache is rebuilt.
+    /// Fresh fragments become eligible again once they are outside every Roche limit.
+    /// Vessels landed on the body are set adrift.
+    pub fn check_tidal_disruption(&mut self) -> bool {
+        let primaries: Vec<Option<usize>> = (0..self.planets.len()).map(|s| self.roche_primary(s)).collect();
+        let mut disrupted = None;
+        for (s, secondary) in self.planets.iter_mut().enumerate() {
+            match primaries[s] {
+                None => secondary.fresh_fragment = false,
+                Some(p) if disrupted.is_none() && !secondary.fresh_fragment && secondary.radius >= MIN_DISRUPTION_RADIUS => {
+                    disrupted = Some((s, p));
+                }
+                Some(_) => {}
+            }
+        }
+
+        let Some((secondary, primary)) = disrupted else {
+            return false;
+        };
+
+        let mut fragments = fragment(&self.planets[secondary], &self.planets[primary]).into_iter();
+        self.planets[secondary] = fragments.next().unwrap();
+        self.planets.extend(fragments);
+
+        // Vessels resting on the body lose their surface and drift free from where they stood
+        for vessel in &mut self.vessels {
+            if vessel.landed.is_some_and(|contact| contact.planet_index == secondary) {
+                vessel.landed = None;
+            }
+        }
+
+        // Recorded states no longer match the set of bodies
+        self.history.clear();
+        self.recalculate_trajectories();
+        true
+    }
+}
@@ -0,0 +1,79 @@
+//! Tidal disruption of bodies inside a Roche limit.
+
+use crate::game::Game;
+use crate::planet::Planet;
+use crate::player::{Player, SurfaceContact, PLAYER_FOOTPRINT};
+use crate::tidal::roche_limit;
+use crate::vector2::Vector2;
+
+/// A loose moon resting well inside the Roche limit of a dense planet
+fn moon_inside_roche_limit() -> Game {
+    let origin = Vector2 { x: 0.0, y: 0.0 };
+    let planet = Planet::new("Planet".to_string(), 100.0, 1.0e12, origin, origin, 0x4080FF);
+    let moon = Planet::new("Moon".to_string(), 40.0, 1.0e9, Vector2 { x: 150.0, y: 0.0 }, origin, 0xAAAAAA);
+    assert!(150.0 < roche_limit(&planet, &moon));
+    let vessel = Player::new(Vector2 { x: 0.0, y: 5000.0 }, origin, 1.0, 0.0);
+    let mut game = Game::new(vec![planet, moon], vec![vessel]);
+    game.prediction_steps = 10;
+    game
+}
+
+#[test]
+fn disruption_fragments_once() {
+    let mut game = moon_inside_roche_limit();
+    assert!(game.check_tidal_disruption());
+    assert_eq!(game.planets.len(), 5);
+
+    // The fragments are as dense as the moon and still inside the limit
+    assert!(!game.check_tidal_disruption());
+    assert_eq!(game.planets.len(), 5);
+}
+
+#[test]
+fn fragments_can_break_up_after_leaving_the_limit() {
+    let mut game = moon_inside_roche_limit();
+    game.check_tidal_disruption();
+    let position = game.planets[1].position;
+
+    game.planets[1].position = Vector2 { x: 1.0e5, y: 0.0 };
+    assert!(!game.check_tidal_disruption());
+    assert!(!game.planets[1].fresh_fragment);
+
+    game.planets[1].position = position;
+    assert!(game.check_tidal_disruption());
+    assert_eq!(game.planets.len(), 8);
+}
+
+/// Total mass and momentum of the bodies
+fn mass_and_momentum(game: &Game) -> (f64, Vector2) {
+    game.planets.iter().fold((0.0, Vector2 { x: 0.0, y: 0.0 }), |(mass, momentum), planet| {
+        (mass + planet.mass, momentum.add(&planet.velocity.scale(planet.mass)))
+    })
+}
+
+#[test]
+fn disruption_conserves_mass_and_momentum() {
+    let mut game = moon_inside_roche_limit();
+    game.planets[1].velocity = Vector2 { x: 3.0, y: 80.0 };
+    let (mass, momentum) = mass_and_momentum(&game);
+
+    assert!(game.check_tidal_disruption());
+    let (fragmented_mass, fragmented_momentum) = mass_and_momentum(&game);
+    assert!((fragmented_mass - mass).abs() <= 1e-12 * mass);
+    assert!(fragmented_momentum.distance(&momentum) <= 1e-12 * momentum.magnitude());
+}
+
+#[test]
+fn vessels_on_a_disrupted_body_are_set_adrift() {
+    let mut game = moon_inside_roche_limit();
+    let moon = &game.planets[1];
+    let normal = Vector2 { x: 1.0, y: 0.0 };
+    let mut vessel = Player::new(moon.position.add(&normal.scale(moon.radius + PLAYER_FOOTPRINT)), moon.velocity, 1.0, 0.0);
+    vessel.landed = Some(SurfaceContact { planet_index: 1, normal, crashed: false });
+    game.vessels.push(vessel);
+
+    assert!(game.check_tidal_disruption());
+    let adrift = game.vessels[1];
+    assert!(adrift.landed.is_none());
+    assert_eq!((adrift.position.x, adrift.position.y), (vessel.position.x, vessel.position.y));
+}
@@ -0,0 +1,49 @@
+//! The cached trajectory prediction and how the game walks along it.
+
+use crate::flyby::{find_flybys, predicted_flybys};
+use crate::game::Game;
+use crate::initial_universe::scenario_by_name;
+use crate::player::Player;
+use crate::vector2::Vector2;
+
+#[test]
+fn extend_without_planets() {
+    let vessel = Player::new(Vector2 { x: 0.0, y: 0.0 }, Vector2 { x: 1.0, y: 0.0 }, 1.0, 0.0);
+    let mut game = Game::new(vec![], vec![vessel]);
+    game.prediction_steps = 10;
+    game.recalculate_trajectories();
+    game.extend_trajectories(5);
+    assert_eq!(game.cached_trajectories.vessel_positions[0].len(), 15);
+}
+
+#[test]
+fn cached_flybys_follow_the_prediction() {
+    let mut game = scenario_by_name("default").expect("built-in scenario exists");
+    game.recalculate_trajectories();
+    assert!(!predicted_flybys(&game, game.active_vessel).is_empty());
+    for _ in 0..10 {
+        game.advance_trajectory();
+    }
+
+    let cached: Vec<(usize, usize)> = predicted_flybys(&game, game.active_vessel).iter().map(|flyby| (flyby.body, flyby.periapsis_index)).collect();
+    let scanned: Vec<(usize, usize)> = find_flybys(&game, game.active_vessel).iter().map(|flyby| (flyby.body, flyby.periapsis_index)).collect();
+    assert_eq!(cached, scanned);
+}
+
+#[test]
+fn flybys_are_cached_for_each_vessel() {
+    let mut game = scenario_by_name("default").expect("built-in scenario exists");
+    game.recalculate_trajectories();
+    let first = game.active_vessel;
+    predicted_flybys(&game, first);
+    game.cycle_active_vessel();
+    let second = game.active_vessel;
+    assert_ne!(first, second);
+
+    // Switching vessels scans the new one's path once, and keeps the first's
+    let cached: Vec<(usize, usize)> = predicted_flybys(&game, second).iter().map(|flyby| (flyby.body, flyby.periapsis_index)).collect();
+    let scanned: Vec<(usize, usize)> = find_flybys(&game, second).iter().map(|flyby| (flyby.body, flyby.periapsis_index)).collect();
+    assert_eq!(cached, scanned);
+    let filled = |vessel: usize| game.cached_trajectories.flybys[vessel].get().is_some();
+    assert!(filled(first) && filled(second));
+}
@@ -0,0 +1,102 @@
+//! Unit system module - maps game units onto SI units for physics and display
+
+use crate::game::DEFAULT_BIG_GRAVITY;
+use serde::{Deserialize, Serialize};
+
+// SI constants
+pub const GRAVITATIONAL_CONSTANT: f64 = 6.674e-11; // m^3 kg^-1 s^-2
+pub const ASTRONOMICAL_UNIT: f64 = 1.495978707e11; // m
+pub const SPEED_OF_LIGHT: f64 = 2.99792458e8; // m/s
+pub const SOLAR_LUMINOSITY: f64 = 3.828e26; // W
+
+/// How many SI units one game unit of length, mass and time represents
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct UnitSystem {
+    pub length_m: f64,
+    pub mass_kg: f64,
+    pub time_s: f64,
+}
+
+impl UnitSystem {
+    pub fn new(length_m: f64, mass_kg: f64, time_s: f64) -> Self {
+        Self { length_m, mass_kg, time_s }
+    }
+
+    /// Pick the mass scale that makes an arbitrary big_gravity physically consistent
+    pub fn from_gravity(big_gravity: f64, length_m: f64, time_s: f64) -> Self {
+        let mass_kg = big_gravity * length_m.powi(3) / (GRAVITATIONAL_CONSTANT * time_s * time_s);
+        Self { length_m, mass_kg, time_s }
+    }
+
+    /// Units of the hand-built scenarios: 1 unit = 1 km, 1 s per second, with
+    /// masses scaled so that big_gravity = DEFAULT_BIG_GRAVITY
+    pub fn game_default() -> Self {
+        Self::from_gravity(DEFAULT_BIG_GRAVITY, 1000.0, 1.0)
+    }
+
+    /// G expressed in this system's units
+    pub fn gravitational_constant(&self) -> f64 {
+        GRAVITATIONAL_CONSTANT * self.mass_kg * self.time_s * self.time_s / self.length_m.powi(3)
+    }
+
+    pub fn length_to_si(&self, length: f64) -> f64 {
+        length * self.length_m
+    }
+
+    pub fn length_from_si(&self, meters: f64) -> f64 {
+        meters / self.length_m
+    }
+
+    pub fn mass_to_si(&self, mass: f64) -> f64 {
+        mass * self.mass_kg
+    }
+
+    pub fn mass_from_si(&self, kilograms: f64) -> f64 {
+        kilograms / self.mass_kg
+    }
+
+    /// Convert a luminosity in watts into the Planet::luminosity radiation
+    /// pressure constant L / (4 pi c), expressed in game units
+    pub fn luminosity_from_si(&self, watts: f64) -> f64 {
+        let pressure_constant = watts / (4.0 * std::f64::consts::PI * SPEED_OF_LIGHT); // N
+        pressure_constant * self.time_s * self.time_s / (self.length_m * self.mass_kg)
+    }
+
+    pub fn force_from_si(&self, newtons: f64) -> f64 {
+        newtons * self.time_s * self.time_s / (self.length_m * self.mass_kg)
+    }
+
+    pub fn speed_to_si(&self, speed: f64) -> f64 {
+        speed * self.length_m / self.time_s
+    }
+
+    pub fn speed_from_si(&self, meters_per_second: f64) -> f64 {
+        meters_per_second * self.time_s / self.length_m
+    }
+
+    /// Format a game-unit length as m, km or AU depending on its size
+    pub fn format_length(&self, length: f64) -> String {
+        let meters = self.length_to_si(length);
+        if meters.abs() >= 0.01 * ASTRONOMICAL_UNIT {
+            format!("{:.3} AU", meters / ASTRONOMICAL_UNIT)
+        } else if meters.abs() >= 1000.0 {
+            format!("{:.0} km", meters / 1000.0)
+        } else {
+            format!("{:.1} m", meters)
+        }
+    }
+
+    /// Format a game-unit speed as m/s or km/s depending on its size
+    pub fn format_speed(&self, speed: f64) -> String {
+        let meters_per_second = self.speed_to_si(speed);
+        if meters_per_second.abs() >= 10000.0 {
+            format!("{:.2} km/s", meters_per_second / 1000.0)
+        } else {
+            format!("{:.1} m/s", meters_per_second)
+        }
+    }
+
+    pub fn format_mass(&self, mass: f64) -> String {
+        format!("{:.2e} kg", self.mass_to_si(mass))
+    }
+}
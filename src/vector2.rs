@@ -1,4 +1,7 @@
-#[derive(Clone, Copy)]
+use crate::det_math;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Vector2 {
     pub x: f64,
     pub y: f64,
@@ -19,6 +22,7 @@ impl Vector2 {
         }
     }
 
+    #[allow(dead_code)]
     pub fn multiply(&self, other: &Vector2) -> Vector2 {
         Vector2 {
             x: self.x * other.x,
@@ -26,6 +30,7 @@ impl Vector2 {
         }
     }
 
+    #[allow(dead_code)]
     pub fn divide(&self, other: &Vector2) -> Vector2 {
         Vector2 {
             x: self.x / other.x,
@@ -37,6 +42,7 @@ impl Vector2 {
         (self.x * self.x + self.y * self.y).sqrt()
     }
 
+    #[allow(dead_code)]
     pub fn normalize(&self) -> Vector2 {
         let magnitude = self.magnitude();
         Vector2 {
@@ -57,14 +63,17 @@ impl Vector2 {
         ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
     }
 
+    #[allow(dead_code)]
     pub fn angle(&self, other: &Vector2) -> f64 {
         self.dot(other) / (self.magnitude() * other.magnitude())
     }
 
     pub fn rotate(&self, angle: f64) -> Vector2 {
+        // Software trig keeps rotations bit-identical across platforms
+        let (sin, cos) = (det_math::sin(angle), det_math::cos(angle));
         Vector2 {
-            x: self.x * angle.cos() - self.y * angle.sin(),
-            y: self.x * angle.sin() + self.y * angle.cos(),
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
         }
     }
 
@@ -75,6 +84,7 @@ impl Vector2 {
         }
     }
 
+    #[allow(dead_code)]
     pub fn lerp(&self, other: &Vector2, t: f64) -> Vector2 {
         Vector2 {
             x: self.x + (other.x - self.x) * t,